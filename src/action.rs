@@ -4,6 +4,7 @@ pub enum Action {
     Click(f64, f64),
     AddCar,
     AddCarSlow,
+    Trace,
     Esc,
 }
 
@@ -1,10 +1,12 @@
 pub mod renderer;
 pub mod fuzzy;
+pub mod tuning;
 
 use crate::bezier;
 use crate::ecs;
 use crate::config::Config;
-use fuzzy::CarFuzzy;
+use fuzzy::{CarFuzzy, CarFuzzyState};
+use crate::fuzzy::Trace;
 
 use bezier::{Point};
 
@@ -48,6 +50,7 @@ pub struct Car {
     pub destination: Point,
     followed_car: Option<ecs::Entity<ForCar>>,
     is_turning_back: bool,
+    fuzzy_state: CarFuzzyState,
 
     pub path_properties: road::PathProperties,
 }
@@ -93,6 +96,7 @@ impl Default for Car {
             destination: Point { x: 100.0, y: 100.0 },
             followed_car: None,
             is_turning_back: false,
+            fuzzy_state: CarFuzzyState::default(),
 
             path_properties: road::PathProperties::default(),
         }
@@ -239,6 +243,7 @@ impl Car {
             destination: dest,
             followed_car: None,
             is_turning_back: false,
+            fuzzy_state: CarFuzzyState::default(),
 
             path_properties,
         }
@@ -278,6 +283,7 @@ impl Car {
             destination: b,
             followed_car: None,
             is_turning_back: false,
+            fuzzy_state: CarFuzzyState::default(),
 
             path_properties,
         })
@@ -302,7 +308,7 @@ impl Car {
         self.direction = output.direction;
     }
 
-    fn fuzzy_set_deviation(&mut self, fuzzy: &mut CarFuzzy, config: &Config) {
+    fn fuzzy_set_deviation(&mut self, fuzzy: &CarFuzzy, config: &Config) {
         let pos = self.position + self.direction * (config.car_width / 2.0);
         let line = bezier::Line { 
             position: pos,
@@ -324,70 +330,85 @@ impl Car {
         let far_dx = if far_dx < 0.0 { 0.0 } else { far_dx };
         let left_dy = if left_dy < 0.0 { 0.0 } else { left_dy };
 
-        fuzzy.fuzzy.set_input(fuzzy.deviation.input, dx / (dx + dy));
-        fuzzy.fuzzy.set_input(fuzzy.road_deviation.input, far_dx / (far_dx + dy));
-        fuzzy.fuzzy.set_input(fuzzy.left_deviation.input, far_dx / (far_dx + left_dy));
+        // Both distances are zero while the car fills the whole lane, in
+        // which case the deviation keeps its last value.
+        let state = &mut self.fuzzy_state;
+        let mut set_share = |input, left: f32, right: f32| {
+            if left + right > 0.0 {
+                fuzzy.set_input(state, input, left / (left + right));
+            }
+        };
+        set_share(fuzzy.deviation.input, dx, dy);
+        set_share(fuzzy.road_deviation.input, far_dx, dy);
+        set_share(fuzzy.left_deviation.input, far_dx, left_dy);
     }
 
-    fn fuzzy_set_light_status_distance(&self, fuzzy: &mut CarFuzzy, road: &Road) {
+    fn fuzzy_set_light_status_distance(&mut self, fuzzy: &CarFuzzy, road: &Road) {
+        let state = &mut self.fuzzy_state;
         if let Some((lane, light_pos)) = road::math::nearest_street_light(
             &self.path_properties.street_lights, self.position, self.direction)
         {
-            fuzzy.fuzzy.set_input(fuzzy.distance.input, (light_pos - self.position).len());
-            fuzzy.fuzzy.set_input(fuzzy.light_status.input, get_lane_light_status(road, lane));
+            fuzzy.set_input(state, fuzzy.distance.input, (light_pos - self.position).len());
+            fuzzy.set_input(state, fuzzy.light_status.input, get_lane_light_status(road, lane));
         }
         else {
-            fuzzy.fuzzy.set_input(fuzzy.distance.input, 1000.0);
-            fuzzy.fuzzy.set_input(fuzzy.light_status.input, 1.0);
+            fuzzy.set_input(state, fuzzy.distance.input, 1000.0);
+            fuzzy.set_input(state, fuzzy.light_status.input, 1.0);
         }
     }
 
     fn fuzzy_set_nearest_car(
-        &self, fuzzy: &mut CarFuzzy,
+        &mut self, fuzzy: &CarFuzzy,
         nearest_car: Option<NearestCar>)
     {
+        let state = &mut self.fuzzy_state;
         if let Some(nearest_car) = nearest_car {
             let distance = (nearest_car.position - self.position).len();
-            fuzzy.fuzzy.set_input(fuzzy.car_distance.input, distance);
-            fuzzy.fuzzy.set_input(fuzzy.car_velocity.input, nearest_car.velocity);
+            fuzzy.set_input(state, fuzzy.car_distance.input, distance);
+            fuzzy.set_input(state, fuzzy.car_velocity.input, nearest_car.velocity);
         }
         else {
-            fuzzy.fuzzy.set_input(fuzzy.car_distance.input, 200.0);
-            fuzzy.fuzzy.set_input(fuzzy.car_velocity.input, 30.0);
+            fuzzy.set_input(state, fuzzy.car_distance.input, 200.0);
+            fuzzy.set_input(state, fuzzy.car_velocity.input, 30.0);
         }
     }
 
     fn fuzzy_set_nearest_opposite_car(
-        &self, fuzzy: &mut CarFuzzy,
+        &mut self, fuzzy: &CarFuzzy,
         nearest_car: Option<NearestOppositeCar>)
     {
+        let state = &mut self.fuzzy_state;
         if let Some(nearest_car) = nearest_car {
             let distance = (nearest_car.position - self.position).len();
-            fuzzy.fuzzy.set_input(fuzzy.car_opposite_distance.input, distance);
-            fuzzy.fuzzy.set_input(fuzzy.car_opposite_velocity.input, nearest_car.velocity);
+            fuzzy.set_input(state, fuzzy.car_opposite_distance.input, distance);
+            fuzzy.set_input(state, fuzzy.car_opposite_velocity.input, nearest_car.velocity);
         }
         else {
-            fuzzy.fuzzy.set_input(fuzzy.car_opposite_distance.input, 200.0);
-            fuzzy.fuzzy.set_input(fuzzy.car_opposite_velocity.input, 30.0);
+            fuzzy.set_input(state, fuzzy.car_opposite_distance.input, 200.0);
+            fuzzy.set_input(state, fuzzy.car_opposite_velocity.input, 30.0);
         }
     }
 
     fn fuzzy_set_side_car(
-        &self, fuzzy: &mut CarFuzzy,
+        &mut self, fuzzy: &CarFuzzy,
         followed_car: Option<FollowedCar>) 
     {
+        let state = &mut self.fuzzy_state;
         if let Some(followed_car) = followed_car {
             let deviation = bezier::dot(followed_car.position - self.position, self.direction);
-            fuzzy.fuzzy.set_input(fuzzy.side_deviation.input, deviation);
+            fuzzy.set_input(state, fuzzy.side_deviation.input, deviation);
         }
         else {
-            fuzzy.fuzzy.set_input(fuzzy.side_deviation.input, 100.0);
+            fuzzy.set_input(state, fuzzy.side_deviation.input, 100.0);
         }
     }
 
     fn fuzzy_output_set_steering(&mut self, fuzzy: &CarFuzzy) {
-        let output = fuzzy.fuzzy.get_output(fuzzy.steering.output);
-        let output = (output - 0.5) / 0.5;
+        // An undefined output keeps the car turning as it did.
+        let output = match fuzzy.get_output(&self.fuzzy_state, fuzzy.steering_output) {
+            Some(output) => (output - 0.5) / 0.5,
+            None => return,
+        };
 
         let angle = f32::abs(output) * std::f32::consts::PI / 2.0;
         let is_turning_left = 
@@ -402,8 +423,10 @@ impl Car {
     }
 
     fn fuzzy_output_set_speed(&mut self, fuzzy: &CarFuzzy) {
-        let output = fuzzy.fuzzy.get_output(fuzzy.speed.output);
-        let output = if f32::is_normal(output) { output } else { 0.0 };
+        let output = match fuzzy.get_output(&self.fuzzy_state, fuzzy.speed.output) {
+            Some(output) => output,
+            None => return,
+        };
         let output = 
             if output < 0.0 {
                 0.0 
@@ -420,7 +443,7 @@ impl Car {
     }
 
     fn do_fuzzy(
-        &mut self, fuzzy: &mut CarFuzzy,
+        &mut self, fuzzy: &CarFuzzy,
         road: &Road, config: &Config,
         nearest_car: Option<NearestCar>,
         nearest_opposite_car: Option<NearestOppositeCar>,
@@ -435,64 +458,42 @@ impl Car {
 
         self.car_type = match self.car_type {
             Slow => {
-                fuzzy.fuzzy.evaluate(fuzzy.simple_rule_set);
+                if let Err(err) = fuzzy.evaluate(&mut self.fuzzy_state, fuzzy.simple_rule_set) {
+                    println!("Warning: {}", err);
+                    return;
+                }
                 Slow
             },
             Normal(state) => {
                 match state {
-                    GoNormal => {
-                        self.fuzzy_set_nearest_opposite_car(fuzzy, nearest_opposite_car);
-
-                        fuzzy.fuzzy.evaluate(fuzzy.normal_rule_set);
-
-                        let output = fuzzy.fuzzy.get_output(fuzzy.go_left_lane.output);
-                        if output > 0.5 && !self.is_turning_back {
-                            self.followed_car =
-                                if let Some(nearest_car) = nearest_car {
-                                    Some(nearest_car.nearest_car)
-                                }
-                                else {
-                                    println!("Warning: FollowedCar");
-                                    None
-                                };
-
-                            Normal(GoLeftLane)
-                        }
-                        else {
-                            Normal(state)
-                        }
-                    },
-                    GoLeftLane => {
-                        fuzzy.fuzzy.evaluate(fuzzy.go_left_lane_rule_set);
-                        let output = fuzzy.fuzzy.get_output(fuzzy.stay_left_lane.output);
-                        if output > 0.5 {
-                            Normal(StayLeftLane)
-                        }
-                        else {
-                            Normal(state)
-                        }
-                    },
-                    StayLeftLane => {
-                        self.fuzzy_set_side_car(fuzzy, followed_car);
-                        fuzzy.fuzzy.evaluate(fuzzy.stay_left_lane_rule_set);
-                        let output = fuzzy.fuzzy.get_output(fuzzy.back_to_right_lane.output);
-                        if output > 0.5 {
-                            Normal(BackToRightLane)
-                        }
-                        else {
-                            Normal(state)
-                        }
-                    },
-                    BackToRightLane => {
-                        fuzzy.fuzzy.evaluate(fuzzy.back_to_right_lane_rule_set);
-                        let output = fuzzy.fuzzy.get_output(fuzzy.go_normal.output);
-                        if output > 0.5 {
-                            Normal(GoNormal)
-                        }
-                        else {
-                            Normal(state)
-                        }
-                    },
+                    GoNormal => self.fuzzy_set_nearest_opposite_car(fuzzy, nearest_opposite_car),
+                    StayLeftLane => self.fuzzy_set_side_car(fuzzy, followed_car),
+                    GoLeftLane | BackToRightLane => (),
+                }
+
+                let stage = fuzzy.lane_stage(state);
+                if let Err(err) = fuzzy.evaluate(&mut self.fuzzy_state, stage.rule_set) {
+                    println!("Warning: {}", err);
+                    return;
+                }
+
+                let output = fuzzy.get_output(&self.fuzzy_state, stage.decision);
+                let leaving_lane = matches!(state, GoNormal);
+                if output.is_some_and(|output| output > 0.5) && !(leaving_lane && self.is_turning_back) {
+                    if leaving_lane {
+                        self.followed_car =
+                            if let Some(nearest_car) = nearest_car {
+                                Some(nearest_car.nearest_car)
+                            }
+                            else {
+                                println!("Warning: FollowedCar");
+                                None
+                            };
+                    }
+                    Normal(stage.next)
+                }
+                else {
+                    Normal(state)
                 }
             },
         };
@@ -523,6 +524,9 @@ pub struct CarSystem {
     pub add_car_type: CarType,
     pub chosen_car: Option<ecs::Entity<ForCar>>,
     old_chosen_car: Option<ecs::Entity<ForCar>>,
+    pub trace_requested: bool,
+    pub trace: Option<Trace>,
+    pub parallel: bool,
 }
 
 fn approx_eq(a: bezier::Point, b: bezier::Point) -> bool {
@@ -634,10 +638,14 @@ impl CarSystem {
             add_car_type: car_type,
             chosen_car: None,
             old_chosen_car: None,
+            trace_requested: false,
+            trace: None,
+            parallel: true,
         }
     }
 
-    pub fn add(&mut self, car: Car) {
+    pub fn add(&mut self, mut car: Car) {
+        car.fuzzy_state = self.fuzzy.new_state();
         let e = self.em.allocate();
         self.chosen_car = Some(e);
         self.cars.set(e, car);
@@ -646,21 +654,38 @@ impl CarSystem {
         self.followed_cars.set(e, None);
     }
 
+    // Swaps in another controller, every car starting over with its inputs.
+    pub fn set_fuzzy(&mut self, fuzzy: CarFuzzy) {
+        self.fuzzy = fuzzy;
+        let fuzzy = &self.fuzzy;
+        self.cars.for_each_mut(false, |_, car| car.fuzzy_state = fuzzy.new_state());
+    }
+
     pub fn update(&mut self, road: &Road, config: &Config) {
         let current = Instant::now();
         let delta = current.duration_since(self.prev_instant);
         let dt: f32 = delta.subsec_micros() as f32 / 1_000_000.0;
         self.prev_instant = current;
 
-        for (e, nearest_car) in self.nearest_cars.iter_mut() {
-            if self.em.is_alive(*e) {
-                let pos = self.cars.get(*e).position;
-                let dir = self.cars.get(*e).direction;
-                let maybe_found_entity = find_nearest_car(&self.em, &self.cars, pos, dir);
+        self.step(dt, road, config);
+    }
+
+    fn step(&mut self, dt: f32, road: &Road, config: &Config) {
+        let parallel = self.parallel;
+        let em = &self.em;
+        let cars = &self.cars;
+
+        // Perception reads only `cars` and writes the per-car snapshots,
+        // the decision phase reads only the snapshots and its own car.
+        self.nearest_cars.for_each_mut(parallel, |e, nearest_car| {
+            if em.is_alive(e) {
+                let pos = cars.get(e).position;
+                let dir = cars.get(e).direction;
+                let maybe_found_entity = find_nearest_car(em, cars, pos, dir);
 
                 if let Some(found_entity) = maybe_found_entity {
-                    let position = self.cars.get(found_entity).position;
-                    let velocity = self.cars.get(found_entity).velocity;
+                    let position = cars.get(found_entity).position;
+                    let velocity = cars.get(found_entity).velocity;
 
                     *nearest_car = Some(NearestCar {
                         position,
@@ -672,17 +697,17 @@ impl CarSystem {
                     *nearest_car = None;
                 }
             }
-        }
+        });
 
-        for (e, nearest_car) in self.nearest_opposite_cars.iter_mut(){
-            if self.em.is_alive(*e) {
-                let pos = self.cars.get(*e).position;
-                let dir = self.cars.get(*e).direction;
-                let maybe_found_entity = find_nearest_opposite_car(&self.em, &self.cars, pos, dir);
+        self.nearest_opposite_cars.for_each_mut(parallel, |e, nearest_car| {
+            if em.is_alive(e) {
+                let pos = cars.get(e).position;
+                let dir = cars.get(e).direction;
+                let maybe_found_entity = find_nearest_opposite_car(em, cars, pos, dir);
 
                 if let Some(found_entity) = maybe_found_entity {
-                    let position = self.cars.get(found_entity).position;
-                    let velocity = self.cars.get(found_entity).velocity;
+                    let position = cars.get(found_entity).position;
+                    let velocity = cars.get(found_entity).velocity;
 
                     *nearest_car = Some(NearestOppositeCar {
                         position,
@@ -693,14 +718,14 @@ impl CarSystem {
                     *nearest_car = None;
                 }
             }
-        }
+        });
 
-        for (e, followed_car) in self.followed_cars.iter_mut() {
-            if self.em.is_alive(*e) {
-                let car = self.cars.get(*e);
+        self.followed_cars.for_each_mut(parallel, |e, followed_car| {
+            if em.is_alive(e) {
+                let car = cars.get(e);
                 *followed_car =
                     if let Some(followed_car) = car.followed_car {
-                        let followed_car = self.cars.get(followed_car);
+                        let followed_car = cars.get(followed_car);
                         Some(FollowedCar {
                             position: followed_car.position,
                         })
@@ -709,18 +734,39 @@ impl CarSystem {
                         None
                     }
             }
-        }
+        });
 
-        for (e, car) in self.cars.iter_mut() {
-            if self.em.is_alive(*e) { 
+        let fuzzy = &self.fuzzy;
+        let nearest_cars = &self.nearest_cars;
+        let nearest_opposite_cars = &self.nearest_opposite_cars;
+        let followed_cars = &self.followed_cars;
+
+        self.cars.for_each_mut(parallel, |e, car| {
+            if em.is_alive(e) {
                 car.do_move(dt, config);
-                let nearest_car = *self.nearest_cars.get(*e);
-                let nearest_opposite_car = *self.nearest_opposite_cars.get(*e);
-                let followed_car = *self.followed_cars.get(*e);
-                car.do_fuzzy(&mut self.fuzzy, 
+                let nearest_car = *nearest_cars.get(e);
+                let nearest_opposite_car = *nearest_opposite_cars.get(e);
+                let followed_car = *followed_cars.get(e);
+                car.do_fuzzy(fuzzy, 
                              road, config, nearest_car, 
                              nearest_opposite_car, followed_car
                              );
+            }
+        });
+
+        for (e, car) in self.cars.iter() {
+            if self.em.is_alive(*e) { 
+                if self.trace_requested && self.chosen_car == Some(*e) {
+                    self.trace = self.fuzzy.trace(&car.fuzzy_state);
+                    if let Some(ref trace) = self.trace {
+                        println!("{}", trace);
+                    }
+                    let rejected = car.fuzzy_state.rejected_inputs();
+                    if rejected > 0 {
+                        println!("rejected inputs: {}", rejected);
+                    }
+                    self.trace_requested = false;
+                }
 
                 if (car.destination - car.position).len() < DESTINATION_EFFECTIVE_RANGE {
                     self.em.deallocate(*e);
@@ -761,3 +807,35 @@ impl CarSystem {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::init;
+
+    fn simulate(road: &Road, config: &Config, parallel: bool) -> Vec<(bool, f32, f32, f32, f32)> {
+        let (_, mut car_system) = init::init(config);
+        car_system.parallel = parallel;
+        for _ in 0..600 {
+            car_system.step(1.0 / 60.0, road, config);
+        }
+
+        car_system.cars.iter()
+            .map(|(e, car)| {
+                (car_system.em.is_alive(*e),
+                 car.position.x, car.position.y, car.velocity, car.angle)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parallel_update() {
+        let config = Config::new();
+        let (road, _) = init::init(&config);
+
+        let sequential = simulate(&road, &config, false);
+        assert!(!sequential.is_empty());
+        assert_eq!(simulate(&road, &config, true), sequential);
+    }
+}
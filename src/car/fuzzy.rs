@@ -11,36 +11,17 @@ mod road_deviation;
 mod booleans;
 mod left_deviation;
 mod side_deviation;
+mod sugeno_steering;
 
 use crate::fuzzy::*;
+use super::CarState;
 
-fn true_fn(x: f32) -> f32 {
-    let x1 = 0.3;
-    let x2 = 0.7;
-
-    if x < x1 {
-        0.0
-    }
-    else if x < x2 {
-        (x - x1) / (x2 - x1)
-    }
-    else {
-        1.0
-    }
+fn true_shape() -> Shape {
+    Shape::RightShoulder { a: 0.3, b: 0.7 }
 }
 
-fn false_fn(x: f32) -> f32 {
-    let x1 = 0.3;
-    let x2 = 0.7;
-    if x < x1 {
-        1.0
-    }
-    else if x < x2 {
-        (x2 - x1) / (x2 - x1)
-    }
-    else {
-        0.0
-    }
+fn false_shape() -> Shape {
+    Shape::PiecewiseLinear(vec![(0.3, 1.0), (0.7, 1.0), (0.7, 0.0)])
 }
 
 pub struct Deviation {
@@ -126,6 +107,7 @@ pub struct SideDeviation {
 
 // Output Fuzzy Sets
 
+#[derive(Copy, Clone)]
 pub struct Steering {
     pub output: OutputId,
     hard_right: OutputSetId,
@@ -135,6 +117,15 @@ pub struct Steering {
     hard_left: OutputSetId,
 }
 
+pub struct SugenoSteering {
+    pub output: OutputId,
+    hard_right: OutputSetId,
+    right: OutputSetId,
+    straight: OutputSetId,
+    left: OutputSetId,
+    hard_left: OutputSetId,
+}
+
 pub struct Speed {
     pub output: OutputId,
     stop: OutputSetId,
@@ -167,11 +158,22 @@ pub struct GoNormal {
     false_: OutputSetId,
 }
 
+// The rule set that drives a car in a lane state and the output that
+// decides when it moves on to the next state.
+#[derive(Copy, Clone)]
+pub struct LaneStage {
+    pub rule_set: RuleSetId,
+    pub decision: OutputId,
+    pub next: CarState,
+}
+
 pub struct CarFuzzy {
     pub fuzzy: Fuzzy,
 
     pub deviation: Deviation,
     pub steering: Steering,
+    pub steering_output: OutputId,
+    pub sugeno_steering: SugenoSteering,
     pub distance: Distance,
     pub speed: Speed,
     pub light_status: LightStatus,
@@ -196,16 +198,73 @@ pub struct CarFuzzy {
 }
 
 
+#[derive(Clone, Default)]
+pub struct CarFuzzyState {
+    inputs: Vec<f32>,
+    scratch: Scratch,
+    evaluation: Evaluation,
+    rejected_inputs: usize,
+}
+
+impl CarFuzzyState {
+    // The number of values set_input refused, which left the input as it was.
+    pub fn rejected_inputs(&self) -> usize {
+        self.rejected_inputs
+    }
+}
+
 impl CarFuzzy {
     pub fn new() -> Self {
+        CarFuzzy::with_operators(Operators::default())
+    }
+
+    pub fn with_operators(operators: Operators) -> Self {
+        CarFuzzy::build(operators, 0.0, false)
+    }
+
+    pub fn with_distance_uncertainty(uncertainty: f32) -> Self {
+        CarFuzzy::build(Operators::default(), uncertainty, false)
+    }
+
+    // Steers with the constant TSK consequents instead of the Mamdani sets.
+    pub fn with_sugeno_steering() -> Self {
+        CarFuzzy::build(Operators::default(), 0.0, true)
+    }
+
+    // Swaps in a controller saved with Fuzzy::to_json, which may change any
+    // shape, rule or operator but must keep the layout of CarFuzzy::new.
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let fuzzy = Fuzzy::from_json(text).map_err(|err| err.to_string())?;
+        let mut car_fuzzy = CarFuzzy::new();
+        car_fuzzy.fuzzy.check_layout(&fuzzy).map_err(|err| err.to_string())?;
+        // A controller saved from with_sugeno_steering steers with the TSK output.
+        car_fuzzy.steering_output =
+            if fuzzy.produces(car_fuzzy.normal_rule_set, car_fuzzy.sugeno_steering.output) {
+                car_fuzzy.sugeno_steering.output
+            }
+            else {
+                car_fuzzy.steering.output
+            };
+        car_fuzzy.fuzzy = fuzzy;
+        Ok(car_fuzzy)
+    }
+
+    fn build(operators: Operators, distance_uncertainty: f32, tsk_steering: bool) -> Self {
         let mut fuzzy = Fuzzy::new();
 
         let deviation = Deviation::new(&mut fuzzy);
         let steering = Steering::new(&mut fuzzy);
+        let sugeno_steering = SugenoSteering::new(&mut fuzzy);
         let distance = Distance::new(&mut fuzzy);
         let speed = Speed::new(&mut fuzzy);
         let light_status = LightStatus::new(&mut fuzzy);
-        let car_distance = CarDistance::new(&mut fuzzy);
+        let car_distance =
+            if distance_uncertainty > 0.0 {
+                CarDistance::new_interval(&mut fuzzy, distance_uncertainty)
+            }
+            else {
+                CarDistance::new(&mut fuzzy)
+            };
         let car_velocity = CarVeclocity::new(&mut fuzzy);
         let car_opposite_distance = CarOppositeDistance::new(&mut fuzzy);
         let car_opposite_velocity = CarOppositeVelocity::new(&mut fuzzy);
@@ -217,89 +276,123 @@ impl CarFuzzy {
         let left_deviation = LeftDeviation::new(&mut fuzzy);
         let side_deviation = SideDeviation::new(&mut fuzzy);
 
-        let rule1 = fuzzy.add_rule(&[deviation.far_left], steering.hard_right);
-        let rule2 = fuzzy.add_rule(&[deviation.left], steering.right);
-        let rule3 = fuzzy.add_rule(&[deviation.middle], steering.straight);
-        let rule4 = fuzzy.add_rule(&[deviation.right], steering.left);
-        let rule5 = fuzzy.add_rule(&[deviation.far_right], steering.hard_left);
-
-        let rule6 = fuzzy.add_rule(&[light_status.green, deviation.middle, car_distance.far], speed.medium);
-        let rule7 = fuzzy.add_rule(&[light_status.green, deviation.left, car_distance.far], speed.slow);
-        let rule7b = fuzzy.add_rule(&[light_status.green, deviation.left, car_distance.medium], speed.slow);
-        let rule8 = fuzzy.add_rule(&[light_status.green, deviation.right, car_distance.far], speed.slow);
-        let rule8b = fuzzy.add_rule(&[light_status.green, deviation.right, car_distance.medium], speed.slow);
-        let rule9 = fuzzy.add_rule(&[light_status.green, deviation.far_left, car_distance.far], speed.slower);
-        let rule9b = fuzzy.add_rule(&[light_status.green, deviation.far_left, car_distance.medium], speed.slower);
-        let rule10 = fuzzy.add_rule(&[light_status.green, deviation.far_right, car_distance.far], speed.slower);
-        let rule10b = fuzzy.add_rule(&[light_status.green, deviation.far_right, car_distance.medium], speed.slower);
-
-        let rule11 = fuzzy.add_rule(&[distance.far, deviation.middle, car_distance.far], speed.medium);
-        let rule12 = fuzzy.add_rule(&[distance.far, deviation.left, car_distance.far], speed.slow);
-        let rule12b = fuzzy.add_rule(&[distance.far, deviation.left, car_distance.medium], speed.slower);
-        let rule13 = fuzzy.add_rule(&[distance.far, deviation.right, car_distance.far], speed.slow);
-        let rule13b = fuzzy.add_rule(&[distance.far, deviation.right, car_distance.medium], speed.slower);
-        let rule14 = fuzzy.add_rule(&[distance.far, deviation.far_left, car_distance.medium_far], speed.slower);
-        let rule15 = fuzzy.add_rule(&[distance.far, deviation.far_right, car_distance.medium_far], speed.slower);
-
-        let rule16 = fuzzy.add_rule(&[light_status.yellow, distance.medium, deviation.middle, car_distance.far], speed.slow);
-        let rule16b = fuzzy.add_rule(&[light_status.yellow, distance.medium, deviation.middle, car_distance.medium], speed.slower);
-        let rule17 = fuzzy.add_rule(&[light_status.yellow, distance.medium, deviation.left, car_distance.medium_far], speed.slower);
-        let rule18 = fuzzy.add_rule(&[light_status.yellow, distance.medium, deviation.right, car_distance.medium_far], speed.slower);
-        let rule19 = fuzzy.add_rule(&[light_status.yellow, distance.medium, deviation.far_left, car_distance.medium_far], speed.slower);
-        let rule20 = fuzzy.add_rule(&[light_status.yellow, distance.medium, deviation.far_right, car_distance.medium_far], speed.slower);
-
-        let rule21 = fuzzy.add_rule(&[light_status.yellow, distance.near], speed.stop);
-
-        let rule22 = fuzzy.add_rule(&[light_status.red, distance.medium, deviation.middle, car_distance.far], speed.slow);
-        let rule22b = fuzzy.add_rule(&[light_status.red, distance.medium, deviation.middle, car_distance.medium], speed.slower);
-        let rule23 = fuzzy.add_rule(&[light_status.red, distance.medium, deviation.left, car_distance.medium_far], speed.slower);
-        let rule24 = fuzzy.add_rule(&[light_status.red, distance.medium, deviation.right, car_distance.medium_far], speed.slower);
-        let rule25 = fuzzy.add_rule(&[light_status.red, distance.medium, deviation.far_left, car_distance.medium_far], speed.slower);
-        let rule26 = fuzzy.add_rule(&[light_status.red, distance.medium, deviation.far_right, car_distance.medium_far], speed.slower);
-
-        let rule27 = fuzzy.add_rule(&[light_status.red, distance.near], speed.stop);
-
-        let rule28 = fuzzy.add_rule(&[light_status.less_green, distance.medium, deviation.middle, car_distance.far], speed.medium);
-        let rule29 = fuzzy.add_rule(&[light_status.less_green, distance.medium, deviation.left, car_distance.far], speed.slow);
-        let rule29b = fuzzy.add_rule(&[light_status.less_green, distance.medium, deviation.left, car_distance.medium], speed.slower);
-        let rule30 = fuzzy.add_rule(&[light_status.less_green, distance.medium, deviation.right, car_distance.far], speed.slow);
-        let rule30b = fuzzy.add_rule(&[light_status.less_green, distance.medium, deviation.right, car_distance.medium], speed.slower);
-        let rule31 = fuzzy.add_rule(&[light_status.less_green, distance.medium, deviation.far_left, car_distance.medium_far], speed.slower);
-        let rule32 = fuzzy.add_rule(&[light_status.less_green, distance.medium, deviation.far_right, car_distance.medium_far], speed.slower);
-
-        let rule33 = fuzzy.add_rule(&[light_status.less_green, distance.near, deviation.middle, car_distance.medium_far], speed.slower);
-        let rule34 = fuzzy.add_rule(&[light_status.less_green, distance.near, deviation.left, car_distance.medium_far], speed.slower);
-        let rule35 = fuzzy.add_rule(&[light_status.less_green, distance.near, deviation.right, car_distance.medium_far], speed.slower);
-        let rule36 = fuzzy.add_rule(&[light_status.less_green, distance.near, deviation.far_left], speed.stop);
-        let rule37 = fuzzy.add_rule(&[light_status.less_green, distance.near, deviation.far_right], speed.stop);
-
-        let rule38 = fuzzy.add_rule(&[light_status.less_red, distance.medium, deviation.middle, car_distance.far], speed.medium);
-        let rule38b = fuzzy.add_rule(&[light_status.less_red, distance.medium, deviation.middle, car_distance.medium], speed.medium);
-        let rule39 = fuzzy.add_rule(&[light_status.less_red, distance.medium, deviation.left, car_distance.medium_far], speed.slow);
-        let rule40 = fuzzy.add_rule(&[light_status.less_red, distance.medium, deviation.right, car_distance.medium_far], speed.slow);
-        let rule41 = fuzzy.add_rule(&[light_status.less_red, distance.medium, deviation.far_left, car_distance.medium_far], speed.slower);
-        let rule42 = fuzzy.add_rule(&[light_status.less_red, distance.medium, deviation.far_right, car_distance.medium_far], speed.slower);
-        let rule43 = fuzzy.add_rule(&[light_status.less_red, distance.near, deviation.far_right], speed.stop);
+        let steer =
+            if tsk_steering {
+                sugeno_steering.as_steering()
+            }
+            else {
+                steering
+            };
+
+        let rule1 = fuzzy.add_rule(&[deviation.far_left], steer.hard_right);
+        let rule2 = fuzzy.add_rule(&[deviation.left], steer.right);
+        let rule3 = fuzzy.add_rule(&[deviation.middle], steer.straight);
+        let rule4 = fuzzy.add_rule(&[deviation.right], steer.left);
+        let rule5 = fuzzy.add_rule(&[deviation.far_right], steer.hard_left);
+
+        // The OR rules below stand for one rule per alternative, so they
+        // fire the same under every `or` and accumulation.
+        let light_rules = [
+            fuzzy.add_rules(Antecedent::all(&[light_status.green, deviation.middle, car_distance.far]), speed.medium),
+            fuzzy.add_rules(
+                Antecedent::all(&[light_status.green])
+                    .and(Antecedent::any(&[deviation.left, deviation.right]))
+                    .and(Antecedent::any(&[car_distance.far, car_distance.medium])),
+                speed.slow),
+            fuzzy.add_rules(
+                Antecedent::all(&[light_status.green])
+                    .and(Antecedent::any(&[deviation.far_left, deviation.far_right]))
+                    .and(Antecedent::any(&[car_distance.far, car_distance.medium])),
+                speed.slower),
+
+            fuzzy.add_rules(Antecedent::all(&[distance.far, deviation.middle, car_distance.far]), speed.medium),
+            fuzzy.add_rules(
+                Antecedent::all(&[distance.far, car_distance.far])
+                    .and(Antecedent::any(&[deviation.left, deviation.right])),
+                speed.slow),
+            fuzzy.add_rules(
+                Antecedent::all(&[distance.far, car_distance.medium])
+                    .and(Antecedent::any(&[deviation.left, deviation.right])),
+                speed.slower),
+            fuzzy.add_rules(
+                Antecedent::all(&[distance.far, car_distance.medium_far])
+                    .and(Antecedent::any(&[deviation.far_left, deviation.far_right])),
+                speed.slower),
+
+            fuzzy.add_rules(Antecedent::all(&[light_status.yellow, distance.medium, deviation.middle, car_distance.far]), speed.slow),
+            fuzzy.add_rules(Antecedent::all(&[light_status.yellow, distance.medium, deviation.middle, car_distance.medium]), speed.slower),
+            fuzzy.add_rules(
+                Antecedent::all(&[light_status.yellow, distance.medium, car_distance.medium_far])
+                    .and(Antecedent::any(&[deviation.left, deviation.right, deviation.far_left, deviation.far_right])),
+                speed.slower),
+
+            fuzzy.add_rules(Antecedent::all(&[light_status.yellow, distance.near]), speed.stop),
+
+            fuzzy.add_rules(Antecedent::all(&[light_status.red, distance.medium, deviation.middle, car_distance.far]), speed.slow),
+            fuzzy.add_rules(Antecedent::all(&[light_status.red, distance.medium, deviation.middle, car_distance.medium]), speed.slower),
+            fuzzy.add_rules(
+                Antecedent::all(&[light_status.red, distance.medium, car_distance.medium_far])
+                    .and(Antecedent::any(&[deviation.left, deviation.right, deviation.far_left, deviation.far_right])),
+                speed.slower),
+
+            fuzzy.add_rules(Antecedent::all(&[light_status.red, distance.near]), speed.stop),
+
+            fuzzy.add_rules(Antecedent::all(&[light_status.less_green, distance.medium, deviation.middle, car_distance.far]), speed.medium),
+            fuzzy.add_rules(
+                Antecedent::all(&[light_status.less_green, distance.medium, car_distance.far])
+                    .and(Antecedent::any(&[deviation.left, deviation.right])),
+                speed.slow),
+            fuzzy.add_rules(
+                Antecedent::all(&[light_status.less_green, distance.medium, car_distance.medium])
+                    .and(Antecedent::any(&[deviation.left, deviation.right])),
+                speed.slower),
+            fuzzy.add_rules(
+                Antecedent::all(&[light_status.less_green, distance.medium, car_distance.medium_far])
+                    .and(Antecedent::any(&[deviation.far_left, deviation.far_right])),
+                speed.slower),
+
+            fuzzy.add_rules(
+                Antecedent::all(&[light_status.less_green, distance.near, car_distance.medium_far])
+                    .and(Antecedent::any(&[deviation.middle, deviation.left, deviation.right])),
+                speed.slower),
+            fuzzy.add_rules(
+                Antecedent::all(&[light_status.less_green, distance.near])
+                    .and(Antecedent::any(&[deviation.far_left, deviation.far_right])),
+                speed.stop),
+
+            fuzzy.add_rules(
+                Antecedent::all(&[light_status.less_red, distance.medium, deviation.middle])
+                    .and(Antecedent::any(&[car_distance.far, car_distance.medium])),
+                speed.medium),
+            fuzzy.add_rules(
+                Antecedent::all(&[light_status.less_red, distance.medium, car_distance.medium_far])
+                    .and(Antecedent::any(&[deviation.left, deviation.right])),
+                speed.slow),
+            fuzzy.add_rules(
+                Antecedent::all(&[light_status.less_red, distance.medium, car_distance.medium_far])
+                    .and(Antecedent::any(&[deviation.far_left, deviation.far_right])),
+                speed.slower),
+            fuzzy.add_rules(Antecedent::all(&[light_status.less_red, distance.near, deviation.far_right]), speed.stop),
+        ].concat();
 
         let rule44 = fuzzy.add_rule(&[car_distance.near], speed.stop);
 
         let rule45 = fuzzy.add_rule(&[distance.far, car_distance.near_medium, car_opposite_distance.far, car_velocity.slow], go_left_lane.true_);
         // let rule45b = fuzzy.add_rule(&[distance.far, car_distance.medium, car_opposite_distance.far, car_velocity.slow], go_left_lane.true_);
-        let rule46 = fuzzy.add_rule(&[car_opposite_distance.near], go_left_lane.false_);
-        let rule47 = fuzzy.add_rule(&[car_distance.medium_far], go_left_lane.false_);
-        let rule48 = fuzzy.add_rule(&[distance.medium], go_left_lane.false_);
-        let rule49 = fuzzy.add_rule(&[distance.near], go_left_lane.false_);
+        let rule46 = fuzzy.add_rules(
+            Antecedent::any(&[car_opposite_distance.near, car_distance.medium_far, distance.medium, distance.near]),
+            go_left_lane.false_);
 
-        let rule50 = fuzzy.add_rule(&[road_deviation.far_left], steering.hard_right);
+        let rule50 = fuzzy.add_rule(&[road_deviation.far_left], steer.hard_right);
         let rule51 = fuzzy.add_rule(&[road_deviation.far_left], speed.slow);
 
-        let rule52 = fuzzy.add_rule(&[road_deviation.middle_left], steering.straight);
+        let rule52 = fuzzy.add_rule(&[road_deviation.middle_left], steer.straight);
         let rule53 = fuzzy.add_rule(&[road_deviation.middle_left], speed.medium);
 
-        let rule54 = fuzzy.add_rule(&[road_deviation.middle], steering.straight);
+        let rule54 = fuzzy.add_rule(&[road_deviation.middle], steer.straight);
         let rule55 = fuzzy.add_rule(&[road_deviation.middle], speed.medium);
 
-        let rule56 = fuzzy.add_rule(&[road_deviation.right], steering.left);
+        let rule56 = fuzzy.add_rule(&[road_deviation.right], steer.left);
         let rule57 = fuzzy.add_rule(&[road_deviation.right], speed.slow);
 
         let rule58 = fuzzy.add_rule(&[left_deviation.middle], stay_left_lane.true_);
@@ -308,11 +401,11 @@ impl CarFuzzy {
         let rule61 = fuzzy.add_rule(&[left_deviation.right], stay_left_lane.false_);
         let rule62 = fuzzy.add_rule(&[left_deviation.right], stay_left_lane.false_);
 
-        let rule63 = fuzzy.add_rule(&[left_deviation.far_left], steering.hard_right);
-        let rule64 = fuzzy.add_rule(&[left_deviation.left], steering.right);
-        let rule65 = fuzzy.add_rule(&[left_deviation.middle], steering.straight);
-        let rule66 = fuzzy.add_rule(&[left_deviation.right], steering.left);
-        let rule67 = fuzzy.add_rule(&[left_deviation.far_right], steering.hard_left);
+        let rule63 = fuzzy.add_rule(&[left_deviation.far_left], steer.hard_right);
+        let rule64 = fuzzy.add_rule(&[left_deviation.left], steer.right);
+        let rule65 = fuzzy.add_rule(&[left_deviation.middle], steer.straight);
+        let rule66 = fuzzy.add_rule(&[left_deviation.right], steer.left);
+        let rule67 = fuzzy.add_rule(&[left_deviation.far_right], steer.hard_left);
 
         let rule68 = fuzzy.add_rule(&[left_deviation.middle], speed.medium);
         let rule69 = fuzzy.add_rule(&[left_deviation.left], speed.slow);
@@ -328,16 +421,16 @@ impl CarFuzzy {
         let rule78 = fuzzy.add_rule(&[side_deviation.not_back], back_to_right_lane.false_);
         let rule79 = fuzzy.add_rule(&[car_distance.near], back_to_right_lane.false_);
 
-        let rule80 = fuzzy.add_rule(&[road_deviation.far_right], steering.hard_left);
+        let rule80 = fuzzy.add_rule(&[road_deviation.far_right], steer.hard_left);
         let rule81 = fuzzy.add_rule(&[road_deviation.far_right], speed.slow);
 
-        let rule82 = fuzzy.add_rule(&[road_deviation.middle_right], steering.straight);
+        let rule82 = fuzzy.add_rule(&[road_deviation.middle_right], steer.straight);
         let rule83 = fuzzy.add_rule(&[road_deviation.middle_right], speed.medium);
 
-        let rule84 = fuzzy.add_rule(&[road_deviation.middle], steering.straight);
+        let rule84 = fuzzy.add_rule(&[road_deviation.middle], steer.straight);
         let rule85 = fuzzy.add_rule(&[road_deviation.middle], speed.medium);
 
-        let rule86 = fuzzy.add_rule(&[road_deviation.left], steering.right);
+        let rule86 = fuzzy.add_rule(&[road_deviation.left], steer.right);
         let rule87 = fuzzy.add_rule(&[road_deviation.left], speed.slow);
 
         let rule88 = fuzzy.add_rule(&[deviation.middle], go_normal.true_);
@@ -346,58 +439,88 @@ impl CarFuzzy {
         let rule91 = fuzzy.add_rule(&[deviation.left], go_normal.false_);
         let rule92 = fuzzy.add_rule(&[deviation.far_left], go_normal.false_);
 
-        let simple_rule_set = fuzzy.add_rule_set(
+        let sugeno_steering_rules = fuzzy.parse_rules("
+            IF deviation IS far_left THEN sugeno_steering IS hard_right
+            IF deviation IS left THEN sugeno_steering IS right
+            IF deviation IS middle THEN sugeno_steering IS straight
+            IF deviation IS right THEN sugeno_steering IS left
+            IF deviation IS far_right THEN sugeno_steering IS hard_left
+        ").unwrap();
+
+        let simple_rule_set = fuzzy.add_rule_set_with_operators(
             &[
-                rule1, rule2, rule3, rule4, rule5,
-                rule6, rule7, rule7b, rule8, rule8b, rule9, rule9b, rule10, rule10b,
-                rule11, rule12, rule12b, rule13, rule13b, rule14, rule15,
-                rule16, rule16b, rule17, rule18, rule19, rule20,
-                rule21, rule22, rule22b, rule23, rule24, rule25, rule26,
-                rule27, rule28, rule29, rule29b, rule30, rule30b, rule31, rule32,
-                rule33, rule34, rule35, rule36, rule37,
-                rule38, rule38b, rule39, rule40, rule41, rule42, rule43,
-                rule44,
-            ]);
-
-        let normal_rule_set = fuzzy.add_rule_set(
+                &[rule1, rule2, rule3, rule4, rule5],
+                &light_rules[..],
+                &[rule44],
+            ].concat(),
+            operators);
+
+        let normal_rule_set = fuzzy.add_rule_set_with_operators(
             &[
-                rule1, rule2, rule3, rule4, rule5,
-                rule6, rule7, rule7b, rule8, rule8b, rule9, rule9b, rule10, rule10b,
-                rule11, rule12, rule12b, rule13, rule13b, rule14, rule15,
-                rule16, rule16b, rule17, rule18, rule19, rule20,
-                rule21, rule22, rule22b, rule23, rule24, rule25, rule26,
-                rule27, rule28, rule29, rule29b, rule30, rule30b, rule31, rule32,
-                rule33, rule34, rule35, rule36, rule37,
-                rule38, rule38b, rule39, rule40, rule41, rule42, rule43,
-                rule44,
-                rule45, rule46, rule47, rule48, rule49,
-            ]);
-
-        let go_left_lane_rule_set = fuzzy.add_rule_set(
+                &[rule1, rule2, rule3, rule4, rule5],
+                &light_rules[..],
+                &[rule44, rule45],
+                &rule46[..],
+            ].concat(),
+            operators);
+
+        let go_left_lane_rule_set = fuzzy.add_rule_set_with_operators(
             &[
                 rule50, rule51, rule52, rule53, rule54, rule55, rule56, rule57,
                 rule58, rule59, rule60, rule61, rule62,
-            ]);
+            ],
+            operators);
 
-        let stay_left_lane_rule_set = fuzzy.add_rule_set(
+        let stay_left_lane_rule_set = fuzzy.add_rule_set_with_operators(
             &[
                 rule63, rule64, rule65, rule66, rule67,
                 rule68, rule69, rule70, rule71, rule72,
                 rule73, rule74, rule75, rule76,
                 rule77, rule78, rule79,
-            ]);
+            ],
+            operators);
 
-        let back_to_right_lane_rule_set = fuzzy.add_rule_set(
+        let back_to_right_lane_rule_set = fuzzy.add_rule_set_with_operators(
             &[
                 rule80, rule81, rule82, rule83, rule84, rule85, rule86, rule87,
                 rule88, rule89, rule90, rule91, rule92,
-            ]);
+            ],
+            operators);
+
+        let sugeno_steering_rule_set = fuzzy.add_rule_set_with_operators(
+            &sugeno_steering_rules,
+            operators);
+
+        let inputs = [
+            deviation.input,
+            distance.input,
+            light_status.input,
+            car_distance.input,
+            car_velocity.input,
+            car_opposite_distance.input,
+            car_opposite_velocity.input,
+            road_deviation.input,
+            left_deviation.input,
+            side_deviation.input,
+        ];
+        for &input in inputs.iter() {
+            fuzzy.set_range_policy(input, RangePolicy::Clamp).unwrap();
+        }
+
+        fuzzy.set_rule_set_name(simple_rule_set, "simple");
+        fuzzy.set_rule_set_name(normal_rule_set, "normal");
+        fuzzy.set_rule_set_name(go_left_lane_rule_set, "go_left_lane");
+        fuzzy.set_rule_set_name(stay_left_lane_rule_set, "stay_left_lane");
+        fuzzy.set_rule_set_name(back_to_right_lane_rule_set, "back_to_right_lane");
+        fuzzy.set_rule_set_name(sugeno_steering_rule_set, "sugeno_steering");
 
         Self {
             fuzzy,
 
             deviation,
             steering,
+            steering_output: steer.output,
+            sugeno_steering,
             distance,
             speed,
             light_status,
@@ -421,4 +544,302 @@ impl CarFuzzy {
             back_to_right_lane_rule_set,
         }
     }
+
+    // Defuzzifies the outputs the car drives with, steering and speed,
+    // to compare how the method changes the way it drives.
+    pub fn set_defuzzifier(&mut self, defuzzifier: Defuzzifier) {
+        for &output in [self.steering_output, self.speed.output].iter() {
+            self.fuzzy.set_defuzzifier(output, defuzzifier);
+        }
+    }
+
+    pub fn set_resolution(&mut self, resolution: usize) {
+        for &output in [self.steering_output, self.speed.output].iter() {
+            self.fuzzy.set_resolution(output, resolution);
+        }
+    }
+
+    pub fn lane_stage(&self, state: CarState) -> LaneStage {
+        match state {
+            CarState::GoNormal => LaneStage {
+                rule_set: self.normal_rule_set,
+                decision: self.go_left_lane.output,
+                next: CarState::GoLeftLane,
+            },
+            CarState::GoLeftLane => LaneStage {
+                rule_set: self.go_left_lane_rule_set,
+                decision: self.stay_left_lane.output,
+                next: CarState::StayLeftLane,
+            },
+            CarState::StayLeftLane => LaneStage {
+                rule_set: self.stay_left_lane_rule_set,
+                decision: self.back_to_right_lane.output,
+                next: CarState::BackToRightLane,
+            },
+            CarState::BackToRightLane => LaneStage {
+                rule_set: self.back_to_right_lane_rule_set,
+                decision: self.go_normal.output,
+                next: CarState::GoNormal,
+            },
+        }
+    }
+
+    // A state holding the current value of every input.
+    pub fn new_state(&self) -> CarFuzzyState {
+        CarFuzzyState {
+            inputs: self.fuzzy.input_values(),
+            ..CarFuzzyState::default()
+        }
+    }
+
+    pub fn set_input(&self, state: &mut CarFuzzyState, input: InputId, value: f32) {
+        if self.fuzzy.set_input_value(&mut state.inputs, input, value).is_err() {
+            state.rejected_inputs += 1;
+        }
+    }
+
+    pub fn input_value(&self, state: &CarFuzzyState, input: InputId) -> Result<f32, FuzzyError> {
+        self.fuzzy.input_value(&state.inputs, input)
+    }
+
+    pub fn evaluate(&self, state: &mut CarFuzzyState, rule_set: RuleSetId)
+        -> Result<(), FuzzyError>
+    {
+        state.evaluation = self.fuzzy
+            .evaluate_with(rule_set, &state.inputs, &mut state.scratch)?;
+        Ok(())
+    }
+
+    // None when no rule fired for the output and its fallback leaves it
+    // undefined.
+    pub fn get_output(&self, state: &CarFuzzyState, output: OutputId) -> Option<f32> {
+        state.evaluation.value(output)
+    }
+
+    pub fn trace(&self, state: &CarFuzzyState) -> Option<Trace> {
+        self.fuzzy.trace_with(&state.scratch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_names() {
+        let car_fuzzy = CarFuzzy::new();
+        let fuzzy = &car_fuzzy.fuzzy;
+
+        assert_eq!(fuzzy.find_input_set(car_fuzzy.car_distance.input, "near"),
+                   Some(car_fuzzy.car_distance.near));
+        assert_eq!(fuzzy.find_output_set(car_fuzzy.go_left_lane.output, "false"),
+                   Some(car_fuzzy.go_left_lane.false_));
+        assert_eq!(fuzzy.input_set_name(car_fuzzy.light_status.less_red), "light_status.less_red");
+        assert_eq!(fuzzy.find_rule_set("back_to_right_lane"),
+                   Some(car_fuzzy.back_to_right_lane_rule_set));
+    }
+
+    #[test]
+    fn test_duplicate_rules() {
+        let car_fuzzy = CarFuzzy::new();
+        let fuzzy = &car_fuzzy.fuzzy;
+        let duplicates: Vec<String> = fuzzy.duplicate_rules().iter()
+            .map(|&(_, rule)| fuzzy.format_rule(rule))
+            .collect();
+
+        assert!(duplicates.contains(
+            &"IF left_deviation IS right THEN stay_left_lane IS false".to_string()));
+        assert!(duplicates.contains(
+            &"IF left_deviation IS left THEN speed IS slow".to_string()));
+        assert!(duplicates.contains(
+            &"IF road_deviation IS middle THEN steering IS straight".to_string()));
+        assert!(fuzzy.conflicting_rules(car_fuzzy.normal_rule_set).is_empty());
+    }
+
+    #[test]
+    fn test_interval_car_distance() {
+        let car_fuzzy = CarFuzzy::with_distance_uncertainty(2.0);
+        let mut state = car_fuzzy.new_state();
+        assert!(car_fuzzy.fuzzy.is_interval_rule_set(car_fuzzy.normal_rule_set));
+        let sugeno_steering_rule_set = car_fuzzy.fuzzy.find_rule_set("sugeno_steering").unwrap();
+        assert!(!car_fuzzy.fuzzy.is_interval_rule_set(sugeno_steering_rule_set));
+
+        let inputs = [
+            (car_fuzzy.deviation.input, 0.4),
+            (car_fuzzy.distance.input, 30.0),
+            (car_fuzzy.light_status.input, 2.5),
+            (car_fuzzy.car_distance.input, 9.0),
+        ];
+        for &(input, value) in inputs.iter() {
+            car_fuzzy.set_input(&mut state, input, value);
+        }
+        car_fuzzy.evaluate(&mut state, car_fuzzy.normal_rule_set).unwrap();
+
+        let speed = car_fuzzy.speed.output;
+        let (left, right) = state.evaluation.interval(speed).unwrap();
+        assert!(left < right);
+        assert_relative_eq!(car_fuzzy.get_output(&state, speed).unwrap(), (left + right) / 2.0);
+    }
+
+    #[test]
+    fn test_lane_stages() {
+        let car_fuzzy = CarFuzzy::new();
+        let stage = car_fuzzy.lane_stage(CarState::StayLeftLane);
+        assert_eq!(stage.rule_set, car_fuzzy.stay_left_lane_rule_set);
+        assert_eq!(stage.decision, car_fuzzy.back_to_right_lane.output);
+
+        let mut lane_state = CarState::GoNormal;
+        let mut names = Vec::new();
+        for _ in 0..5 {
+            let stage = car_fuzzy.lane_stage(lane_state);
+            names.push(car_fuzzy.fuzzy.rule_set_name(stage.rule_set).to_string());
+            lane_state = stage.next;
+        }
+        assert_eq!(names,
+                   ["normal", "go_left_lane", "stay_left_lane", "back_to_right_lane", "normal"]);
+    }
+
+    #[test]
+    fn test_or_rules() {
+        let speed = |accumulation| {
+            let car_fuzzy =
+                CarFuzzy::with_operators(Operators { accumulation, ..Operators::default() });
+            let mut state = car_fuzzy.new_state();
+            car_fuzzy.set_input(&mut state, car_fuzzy.light_status.input, 1.0);
+            car_fuzzy.set_input(&mut state, car_fuzzy.distance.input, 40.0);
+            car_fuzzy.set_input(&mut state, car_fuzzy.deviation.input, 0.45);
+            car_fuzzy.set_input(&mut state, car_fuzzy.car_distance.input, 22.0);
+            car_fuzzy.evaluate(&mut state, car_fuzzy.normal_rule_set).unwrap();
+            car_fuzzy.get_output(&state, car_fuzzy.speed.output).unwrap()
+        };
+
+        // The rules written out one by one give these speeds at these inputs.
+        assert_relative_eq!(speed(SNorm::Maximum), 0.5177, epsilon = 1e-4);
+        assert_relative_eq!(speed(SNorm::ProbabilisticSum), 0.5565, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_sugeno_steering() {
+        let mamdani = CarFuzzy::new();
+        let sugeno = CarFuzzy::with_sugeno_steering();
+        assert_eq!(mamdani.steering_output, mamdani.steering.output);
+        assert_eq!(sugeno.steering_output, sugeno.sugeno_steering.output);
+        let loaded = CarFuzzy::from_json(&sugeno.fuzzy.to_json()).unwrap();
+        assert_eq!(loaded.steering_output, sugeno.sugeno_steering.output);
+
+        for &deviation in [0.05, 0.3, 0.5, 0.7, 0.95].iter() {
+            let steer = |car_fuzzy: &CarFuzzy| {
+                let mut state = car_fuzzy.new_state();
+                car_fuzzy.set_input(&mut state, car_fuzzy.deviation.input, deviation);
+                car_fuzzy.evaluate(&mut state, car_fuzzy.normal_rule_set).unwrap();
+                car_fuzzy.get_output(&state, car_fuzzy.steering_output).unwrap() - 0.5
+            };
+            let (mamdani, sugeno) = (steer(&mamdani), steer(&sugeno));
+            assert!(mamdani * sugeno > 0.0 || (mamdani.abs() < 1e-3 && sugeno.abs() < 1e-3),
+                    "{}: {} {}", deviation, mamdani, sugeno);
+        }
+    }
+
+    #[test]
+    fn test_rejected_inputs() {
+        let car_fuzzy = CarFuzzy::new();
+        let mut state = car_fuzzy.new_state();
+        car_fuzzy.set_input(&mut state, car_fuzzy.deviation.input, 0.3);
+        car_fuzzy.set_input(&mut state, car_fuzzy.deviation.input, f32::NAN);
+        assert_eq!(state.rejected_inputs(), 1);
+        assert_relative_eq!(car_fuzzy.input_value(&state, car_fuzzy.deviation.input).unwrap(), 0.3);
+    }
+
+    #[test]
+    fn test_undefined_output() {
+        let mut car_fuzzy = CarFuzzy::new();
+        car_fuzzy.fuzzy.set_fallback(car_fuzzy.speed.output, Fallback::Undefined);
+        let loaded = CarFuzzy::from_json(&car_fuzzy.fuzzy.to_json()).unwrap();
+
+        let mut state = loaded.new_state();
+        loaded.set_input(&mut state, loaded.deviation.input, 0.4);
+        let sugeno_steering_rule_set = loaded.fuzzy.find_rule_set("sugeno_steering").unwrap();
+        loaded.evaluate(&mut state, sugeno_steering_rule_set).unwrap();
+        assert!(loaded.get_output(&state, loaded.sugeno_steering.output).is_some());
+        assert_eq!(loaded.get_output(&state, loaded.speed.output), None);
+    }
+
+    #[test]
+    fn test_uninitialized_state() {
+        let car_fuzzy = CarFuzzy::new();
+        let mut state = CarFuzzyState::default();
+        assert_eq!(
+            car_fuzzy.evaluate(&mut state, car_fuzzy.normal_rule_set),
+            Err(FuzzyError::InputCount { expected: car_fuzzy.fuzzy.input_values().len(), found: 0 }));
+    }
+
+    #[test]
+    fn test_defuzzifier() {
+        let mut car_fuzzy = CarFuzzy::new();
+        let mut state = car_fuzzy.new_state();
+        car_fuzzy.set_input(&mut state, car_fuzzy.deviation.input, 0.4);
+        car_fuzzy.set_input(&mut state, car_fuzzy.distance.input, 30.0);
+        car_fuzzy.set_input(&mut state, car_fuzzy.light_status.input, 2.5);
+        car_fuzzy.set_input(&mut state, car_fuzzy.car_distance.input, 12.0);
+
+        let mut speeds = Vec::new();
+        for &defuzzifier in [Defuzzifier::Centroid, Defuzzifier::LargestOfMaximum].iter() {
+            car_fuzzy.set_defuzzifier(defuzzifier);
+            car_fuzzy.set_resolution(200);
+            car_fuzzy.evaluate(&mut state, car_fuzzy.normal_rule_set).unwrap();
+            speeds.push(car_fuzzy.get_output(&state, car_fuzzy.speed.output).unwrap());
+        }
+        assert!(speeds[0] < speeds[1]);
+    }
+
+    #[test]
+    fn test_json_controller() {
+        let mut car_fuzzy = CarFuzzy::new();
+        let weight = car_fuzzy.fuzzy.parameters().into_iter()
+            .find(|p| matches!(p, Parameter::RuleWeight(_)))
+            .unwrap();
+        car_fuzzy.fuzzy.set_parameters(&[weight], &[0.5]);
+        let text = car_fuzzy.fuzzy.to_json();
+        let loaded = CarFuzzy::from_json(&text).unwrap();
+        assert_eq!(loaded.fuzzy.to_json(), text);
+
+        let mut state = car_fuzzy.new_state();
+        let mut loaded_state = loaded.new_state();
+        car_fuzzy.set_input(&mut state, car_fuzzy.deviation.input, 0.4);
+        loaded.set_input(&mut loaded_state, loaded.deviation.input, 0.4);
+        car_fuzzy.evaluate(&mut state, car_fuzzy.normal_rule_set).unwrap();
+        loaded.evaluate(&mut loaded_state, loaded.normal_rule_set).unwrap();
+        assert_eq!(car_fuzzy.get_output(&state, car_fuzzy.steering.output),
+                   loaded.get_output(&loaded_state, loaded.steering.output));
+
+        let renamed = text.replacen("\"name\": \"steering\"", "\"name\": \"turn\"", 1);
+        assert_eq!(CarFuzzy::from_json(&renamed).err().unwrap(),
+                   "expected output `steering`, found output `turn`");
+    }
+
+    #[test]
+    fn test_fcl_export() {
+        let car_fuzzy = CarFuzzy::new();
+        let mut state = car_fuzzy.new_state();
+        let mut loaded = Fuzzy::from_fcl(&car_fuzzy.fuzzy.to_fcl("car")).unwrap();
+
+        let inputs = [
+            (car_fuzzy.deviation.input, 0.4),
+            (car_fuzzy.distance.input, 30.0),
+            (car_fuzzy.light_status.input, 2.5),
+            (car_fuzzy.car_distance.input, 12.0),
+        ];
+        for &(input, value) in inputs.iter() {
+            car_fuzzy.set_input(&mut state, input, value);
+            loaded.set_input(input, value).unwrap();
+        }
+
+        car_fuzzy.evaluate(&mut state, car_fuzzy.normal_rule_set).unwrap();
+        loaded.evaluate(car_fuzzy.normal_rule_set).unwrap();
+        for &output in [car_fuzzy.steering.output, car_fuzzy.speed.output].iter() {
+            assert_relative_eq!(car_fuzzy.get_output(&state, output).unwrap(), loaded.get_output(output).unwrap());
+        }
+    }
 }
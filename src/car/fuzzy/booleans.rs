@@ -5,10 +5,15 @@ impl GoLeftLane {
         let output = fuzzy.add_output(0.0, 1.0);
 
         let true_ = fuzzy.add_output_set(
-            output, Box::new(true_fn));
+            output, true_shape());
 
         let false_ = fuzzy.add_output_set(
-            output, Box::new(false_fn));
+            output, false_shape());
+
+        fuzzy.set_output_name(output, "go_left_lane");
+        fuzzy.set_fallback(output, Fallback::Default(0.0));
+        fuzzy.set_output_set_name(true_, "true");
+        fuzzy.set_output_set_name(false_, "false");
 
         Self {
             output,
@@ -23,10 +28,15 @@ impl StayLeftLane {
         let output = fuzzy.add_output(0.0, 1.0);
 
         let true_ = fuzzy.add_output_set(
-            output, Box::new(true_fn));
+            output, true_shape());
 
         let false_ = fuzzy.add_output_set(
-            output, Box::new(false_fn));
+            output, false_shape());
+
+        fuzzy.set_output_name(output, "stay_left_lane");
+        fuzzy.set_fallback(output, Fallback::Default(0.0));
+        fuzzy.set_output_set_name(true_, "true");
+        fuzzy.set_output_set_name(false_, "false");
 
         Self {
             output,
@@ -41,10 +51,15 @@ impl BackToRightLane {
         let output = fuzzy.add_output(0.0, 1.0);
 
         let true_ = fuzzy.add_output_set(
-            output, Box::new(true_fn));
+            output, true_shape());
 
         let false_ = fuzzy.add_output_set(
-            output, Box::new(false_fn));
+            output, false_shape());
+
+        fuzzy.set_output_name(output, "back_to_right_lane");
+        fuzzy.set_fallback(output, Fallback::Default(0.0));
+        fuzzy.set_output_set_name(true_, "true");
+        fuzzy.set_output_set_name(false_, "false");
 
         Self {
             output,
@@ -59,10 +74,15 @@ impl GoNormal {
         let output = fuzzy.add_output(0.0, 1.0);
 
         let true_ = fuzzy.add_output_set(
-            output, Box::new(true_fn));
+            output, true_shape());
 
         let false_ = fuzzy.add_output_set(
-            output, Box::new(false_fn));
+            output, false_shape());
+
+        fuzzy.set_output_name(output, "go_normal");
+        fuzzy.set_fallback(output, Fallback::Default(0.0));
+        fuzzy.set_output_set_name(true_, "true");
+        fuzzy.set_output_set_name(false_, "false");
 
         Self {
             output,
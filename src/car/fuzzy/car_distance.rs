@@ -1,102 +1,40 @@
 use super::*;
 
 impl CarDistance {
-
-    fn near_fn(x: f32) -> f32 {
-        let x1 = 5.0;
-        let x2 = 10.0;
-        if x < x1 {
-            1.0
-        }
-        else if x < x2 {
-            (x2 - x) / (x2 - x1)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn near_medium_fn(x: f32) -> f32 {
-        let x1 = 10.0;
-        let x2 = 20.0;
-        if x < x1 {
-            1.0
-        }
-        else if x < x2 {
-            (x2 - x) / (x2 - x1)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn medium_fn(x: f32) -> f32 {
-        let x1 = 5.0;
-        let x2 = 10.0;
-        let x3 = 25.0;
-        let x4 = 30.0;
-        if x < x1 {
-            0.0
-        }
-        else if x < x2 {
-            (x - x1) / (x2 - x1)
-        }
-        else if x < x3 {
-            1.0
-        }
-        else if x < x4 {
-            (x4 - x) / (x4 - x3)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn far_fn(x: f32) -> f32 {
-        let x1 = 20.0;
-        let x2 = 25.0;
-        if x < x1 {
-            0.0
-        }
-        else if x < x2 {
-            (x - x1) / (x2 - x1)
-        }
-        else {
-            1.0
-        }
+    pub fn new(fuzzy: &mut Fuzzy) -> Self {
+        Self::with_sets(fuzzy, MembershipFunction::from)
     }
 
-    fn medium_far_fn(x: f32) -> f32 {
-        let x1 = 5.0;
-        let x2 = 10.0;
-        if x < x1 {
-            0.0
-        }
-        else if x < x2 {
-            (x - x1) / (x2 - x1)
-        }
-        else {
-            1.0
-        }
+    pub fn new_interval(fuzzy: &mut Fuzzy, uncertainty: f32) -> Self {
+        Self::with_sets(fuzzy, |shape| MembershipFunction::interval_around(shape, uncertainty))
     }
 
-    pub fn new(fuzzy: &mut Fuzzy) -> Self {
+    fn with_sets<F>(fuzzy: &mut Fuzzy, set: F) -> Self
+        where F: Fn(Shape) -> MembershipFunction
+    {
         let input = fuzzy.add_input(0.0, 200.0);
 
         let near = fuzzy.add_input_set(
-            input, Box::new(CarDistance::near_fn));
+            input, set(Shape::LeftShoulder { a: 5.0, b: 10.0 }));
 
         let near_medium = fuzzy.add_input_set(
-            input, Box::new(CarDistance::near_medium_fn));
+            input, set(Shape::LeftShoulder { a: 10.0, b: 20.0 }));
 
         let medium = fuzzy.add_input_set(
-            input, Box::new(CarDistance::medium_fn));
+            input, set(Shape::Trapezoid { a: 5.0, b: 10.0, c: 25.0, d: 30.0 }));
 
         let far = fuzzy.add_input_set(
-            input, Box::new(CarDistance::far_fn));
+            input, set(Shape::RightShoulder { a: 20.0, b: 25.0 }));
 
         let medium_far = fuzzy.add_input_set(
-            input, Box::new(CarDistance::medium_far_fn));
+            input, set(Shape::RightShoulder { a: 5.0, b: 10.0 }));
+
+        fuzzy.set_input_name(input, "car_distance");
+        fuzzy.set_input_set_name(near, "near");
+        fuzzy.set_input_set_name(near_medium, "near_medium");
+        fuzzy.set_input_set_name(medium, "medium");
+        fuzzy.set_input_set_name(far, "far");
+        fuzzy.set_input_set_name(medium_far, "medium_far");
 
         Self {
             input,
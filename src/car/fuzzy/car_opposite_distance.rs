@@ -1,42 +1,18 @@
 use super::*;
 
 impl CarOppositeDistance {
-    fn near_fn(x: f32) -> f32 {
-        let x1 = 20.0;
-        let x2 = 30.0;
-        if x < x1 {
-            1.0
-        }
-        else if x < x2 {
-            (x2 - x) / (x2 - x1)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn far_fn(x: f32) -> f32 {
-        let x1 = 20.0;
-        let x2 = 40.0;
-        if x < x1 {
-            0.0
-        }
-        else if x < x2 {
-            (x - x1) / (x2 - x1)
-        }
-        else {
-            1.0
-        }
-    }
-
     pub fn new(fuzzy: &mut Fuzzy) -> Self {
         let input = fuzzy.add_input(0.0, 400.0);
 
         let near = fuzzy.add_input_set(
-            input, Box::new(CarOppositeDistance::near_fn));
+            input, Shape::LeftShoulder { a: 20.0, b: 30.0 });
 
         let far = fuzzy.add_input_set(
-            input, Box::new(CarOppositeDistance::far_fn));
+            input, Shape::RightShoulder { a: 20.0, b: 40.0 });
+
+        fuzzy.set_input_name(input, "car_opposite_distance");
+        fuzzy.set_input_set_name(near, "near");
+        fuzzy.set_input_set_name(far, "far");
 
         Self {
             input,
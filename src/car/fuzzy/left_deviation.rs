@@ -1,110 +1,30 @@
 use super::*;
 
 impl LeftDeviation {
-    fn far_left_fn(x: f32) -> f32 {
-        let x1 = 0.25;
-        let x2 = 0.4;
-
-        if x < x1 {
-            1.0
-        }
-        else if x < x2 {
-            (x2 - x) / (x2 - x1)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn left_fn(x: f32) -> f32 {
-        let x1 = 0.25;
-        let x2 = 0.4;
-        let x3 = 0.5;
-
-        if x < x1 {
-            0.0
-        }
-        else if x < x2 {
-            (x - x1) / (x2 - x1)
-        }
-        else if x < x3 {
-            (x3 - x) / (x3 - x2)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn middle_fn(x: f32) -> f32 {
-        let x1 = 0.4;
-        let x2 = 0.5;
-        let x3 = 0.6;
-
-        if x < x1 {
-            0.0
-        }
-        else if x < x2 {
-            (x - x1) / (x2 - x1)
-        }
-        else if x < x3 {
-            (x3 - x) / (x3 - x2)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn right_fn(x: f32) -> f32 {
-        let x1 = 0.5;
-        let x2 = 0.6;
-        let x3 = 0.75;
-
-        if x < x1 {
-            0.0
-        }
-        else if x < x2 {
-            (x - x1) / (x2 - x1)
-        }
-        else if x < x3 {
-            (x3 - x) / (x3 - x2)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn far_right_fn(x: f32) -> f32 {
-        let x1 = 0.6;
-        let x2 = 0.75;
-
-        if x < x1 {
-            0.0
-        }
-        else if x < x2 {
-            (x - x1) / (x2 - x1)
-        }
-        else {
-            1.0
-        }
-    }
-
     pub fn new(fuzzy: &mut Fuzzy) -> Self {
         let input = fuzzy.add_input(0.0, 1.0);
 
         let far_left = fuzzy.add_input_set(
-            input, Box::new(LeftDeviation::far_left_fn));
+            input, Shape::LeftShoulder { a: 0.25, b: 0.4 });
 
         let left = fuzzy.add_input_set(
-            input, Box::new(LeftDeviation::left_fn));
+            input, Shape::Triangle { a: 0.25, b: 0.4, c: 0.5 });
 
         let middle = fuzzy.add_input_set(
-            input, Box::new(LeftDeviation::middle_fn));
+            input, Shape::Triangle { a: 0.4, b: 0.5, c: 0.6 });
 
         let right = fuzzy.add_input_set(
-            input, Box::new(LeftDeviation::right_fn));
+            input, Shape::Triangle { a: 0.5, b: 0.6, c: 0.75 });
 
         let far_right = fuzzy.add_input_set(
-            input, Box::new(LeftDeviation::far_right_fn));
+            input, Shape::RightShoulder { a: 0.6, b: 0.75 });
+
+        fuzzy.set_input_name(input, "left_deviation");
+        fuzzy.set_input_set_name(far_left, "far_left");
+        fuzzy.set_input_set_name(left, "left");
+        fuzzy.set_input_set_name(middle, "middle");
+        fuzzy.set_input_set_name(right, "right");
+        fuzzy.set_input_set_name(far_right, "far_right");
 
         LeftDeviation {
             input,
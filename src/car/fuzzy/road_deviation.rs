@@ -1,142 +1,38 @@
 use super::*;
 
 impl RoadDeviation {
-    fn far_left_fn(x: f32) -> f32 {
-        let x1 = 0.1;
-        let x2 = 0.25;
-        if x < x1 {
-            1.0
-        }
-        else if x < x2 {
-            (x2 - x) / (x2 - x1)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn middle_left_fn(x: f32) -> f32 {
-        let x1 = 0.1;
-        let x2 = 0.25;
-        let x3 = 0.4;
-
-        if x < x1 {
-            0.0
-        }
-        else if x < x2  {
-            (x - x1) / (x2 - x1)
-        }
-        else if x < x3 {
-            (x3 - x) / (x3 - x2)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn left_fn(x: f32) -> f32 {
-        let x1 = 0.35;
-        let x2 = 0.5;
-        if x < x1 {
-            1.0
-        }
-        else if x < x2 {
-            (x2 - x) / (x2 - x1)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn middle_fn(x: f32) -> f32 {
-        let x1 = 0.35;
-        let x2 = 0.5;
-        let x3 = 0.65;
-
-        if x < x1 {
-            0.0
-        }
-        else if x < x2  {
-            (x - x1) / (x2 - x1)
-        }
-        else if x < x3 {
-            (x3 - x) / (x3 - x2)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn right_fn(x: f32) -> f32 {
-        let x1 = 0.5;
-        let x2 = 0.65;
-        if x < x1 {
-            0.0
-        }
-        else if x < x2 {
-            (x - x1) / (x2 - x1)
-        }
-        else {
-            1.0
-        }
-    }
-
-    fn middle_right_fn(x: f32) -> f32 {
-        let x1 = 0.6;
-        let x2 = 0.75;
-        let x3 = 0.9;
-
-        if x < x1 {
-            0.0
-        }
-        else if x < x2  {
-            (x - x1) / (x2 - x1)
-        }
-        else if x < x3 {
-            (x3 - x) / (x3 - x2)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn far_right_fn(x: f32) -> f32 {
-        let x1 = 0.75;
-        let x2 = 0.9;
-        if x < x1 {
-            0.0
-        }
-        else if x < x2 {
-            (x - x1) / (x2 - x1)
-        }
-        else {
-            1.0
-        }
-    }
-
     pub fn new(fuzzy: &mut Fuzzy) -> Self {
         let input = fuzzy.add_input(0.0, 1.0);
 
         let far_left = fuzzy.add_input_set(
-            input, Box::new(RoadDeviation::far_left_fn));
+            input, Shape::LeftShoulder { a: 0.1, b: 0.25 });
 
         let middle_left = fuzzy.add_input_set(
-            input, Box::new(RoadDeviation::middle_left_fn));
+            input, Shape::Triangle { a: 0.1, b: 0.25, c: 0.4 });
 
         let left = fuzzy.add_input_set(
-            input, Box::new(RoadDeviation::left_fn));
+            input, Shape::LeftShoulder { a: 0.35, b: 0.5 });
 
         let middle = fuzzy.add_input_set(
-            input, Box::new(RoadDeviation::middle_fn));
+            input, Shape::Triangle { a: 0.35, b: 0.5, c: 0.65 });
 
         let right = fuzzy.add_input_set(
-            input, Box::new(RoadDeviation::right_fn));
+            input, Shape::RightShoulder { a: 0.5, b: 0.65 });
 
         let middle_right = fuzzy.add_input_set(
-            input, Box::new(RoadDeviation::middle_right_fn));
+            input, Shape::Triangle { a: 0.6, b: 0.75, c: 0.9 });
 
         let far_right = fuzzy.add_input_set(
-            input, Box::new(RoadDeviation::far_right_fn));
+            input, Shape::RightShoulder { a: 0.75, b: 0.9 });
+
+        fuzzy.set_input_name(input, "road_deviation");
+        fuzzy.set_input_set_name(far_left, "far_left");
+        fuzzy.set_input_set_name(middle_left, "middle_left");
+        fuzzy.set_input_set_name(left, "left");
+        fuzzy.set_input_set_name(middle, "middle");
+        fuzzy.set_input_set_name(right, "right");
+        fuzzy.set_input_set_name(middle_right, "middle_right");
+        fuzzy.set_input_set_name(far_right, "far_right");
 
         Self {
             input,
@@ -1,88 +1,27 @@
 use super::*;
 
 impl Speed {
-    fn stop_fn(x: f32) -> f32 {
-        let x1 = 0.0;
-        let x2 = 0.05;
-
-        if x < x1 {
-            1.0
-        }
-        else if x < x2 {
-            (x2 - x) / (x2 - x1)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn slower_fn(x: f32) -> f32 {
-        let x1 = 0.025;
-        let x2 = 0.25;
-        let x3 = 0.5;
-
-        if x < x1 {
-            0.0
-        }
-        else if x < x2 {
-            (x - x1) / (x2 - x1)
-        }
-        else if x < x3 {
-            (x3 - x) / (x3 - x2)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn slow_fn(x: f32) -> f32 {
-        let x1 = 0.3;
-        let x2 = 0.6;
-        let x3 = 0.8;
-
-        if x < x1 {
-            0.0
-        }
-        else if x < x2 {
-            (x - x1) / (x2 - x1)
-        }
-        else if x < x3 {
-            (x3 - x) / (x3 - x2)
-        }
-        else {
-            0.0
-        }
-    }
-
-    fn medium_fn(x: f32) -> f32 {
-        let x1 = 0.7;
-        let x2 = 0.9;
-
-        if x < x1 {
-            0.0
-        }
-        else if x < x2 {
-            (x - x1) / (x2 - x1)
-        }
-        else {
-            1.0
-        }
-    }
-
     pub fn new(fuzzy: &mut Fuzzy) -> Self {
         let output = fuzzy.add_output(0.0, 1.0);
 
         let stop = fuzzy.add_output_set(
-            output, Box::new(Speed::stop_fn));
+            output, Shape::LeftShoulder { a: 0.0, b: 0.05 });
 
         let slower = fuzzy.add_output_set(
-            output, Box::new(Speed::slower_fn));
+            output, Shape::Triangle { a: 0.025, b: 0.25, c: 0.5 });
 
         let slow = fuzzy.add_output_set(
-            output, Box::new(Speed::slow_fn));
+            output, Shape::Triangle { a: 0.3, b: 0.6, c: 0.8 });
 
         let medium = fuzzy.add_output_set(
-            output, Box::new(Speed::medium_fn));
+            output, Shape::RightShoulder { a: 0.7, b: 0.9 });
+
+        fuzzy.set_output_name(output, "speed");
+        fuzzy.set_fallback(output, Fallback::Default(0.0));
+        fuzzy.set_output_set_name(stop, "stop");
+        fuzzy.set_output_set_name(slower, "slower");
+        fuzzy.set_output_set_name(slow, "slow");
+        fuzzy.set_output_set_name(medium, "medium");
 
         Self {
             output,
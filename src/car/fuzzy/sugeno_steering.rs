@@ -0,0 +1,52 @@
+use super::*;
+
+impl SugenoSteering {
+    pub fn new(fuzzy: &mut Fuzzy) -> Self {
+        let output = fuzzy.add_sugeno_output(0.0, 1.0);
+
+        let hard_right = fuzzy.add_consequent(
+            output, Consequent::Constant(0.85));
+
+        let right = fuzzy.add_consequent(
+            output, Consequent::Constant(0.6));
+
+        let straight = fuzzy.add_consequent(
+            output, Consequent::Constant(0.5));
+
+        let left = fuzzy.add_consequent(
+            output, Consequent::Constant(0.4));
+
+        let hard_left = fuzzy.add_consequent(
+            output, Consequent::Constant(0.15));
+
+        fuzzy.set_output_name(output, "sugeno_steering");
+        fuzzy.set_fallback(output, Fallback::Default(0.5));
+        fuzzy.set_output_set_name(hard_right, "hard_right");
+        fuzzy.set_output_set_name(right, "right");
+        fuzzy.set_output_set_name(straight, "straight");
+        fuzzy.set_output_set_name(left, "left");
+        fuzzy.set_output_set_name(hard_left, "hard_left");
+
+        Self {
+            output,
+            hard_right,
+            right,
+            straight,
+            left,
+            hard_left,
+        }
+    }
+
+    // The TSK sets in the place of the Mamdani ones, for the car's rules to
+    // conclude on.
+    pub fn as_steering(&self) -> Steering {
+        Steering {
+            output: self.output,
+            hard_right: self.hard_right,
+            right: self.right,
+            straight: self.straight,
+            left: self.left,
+            hard_left: self.hard_left,
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::SmallRng;
+use rand::distributions::StandardNormal;
+use rayon::prelude::*;
+
+use crate::config::Config;
+use crate::ecs;
+use crate::fuzzy::Parameter;
+use crate::init;
+use super::{CarSystem, ForCar};
+use super::fuzzy::CarFuzzy;
+
+const DT: f32 = 1.0 / 60.0;
+const TOURNAMENT_SIZE: usize = 3;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tuning {
+    pub seed: u64,
+    pub population: usize,
+    pub generations: usize,
+    pub steps: usize,
+    pub elites: usize,
+    pub mutation_rate: f32,
+    pub mutation_scale: f32,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Score {
+    pub arrivals: usize,
+    pub collisions: usize,
+    pub average_speed: f32,
+    pub average_deviation: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    pub values: Vec<f32>,
+    pub score: Score,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TuningReport {
+    pub parameters: Vec<Parameter>,
+    pub best: Candidate,
+    pub history: Vec<f32>,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning {
+            seed: 0,
+            population: 16,
+            generations: 10,
+            steps: 7200,
+            elites: 2,
+            mutation_rate: 0.1,
+            mutation_scale: 0.05,
+        }
+    }
+}
+
+impl Score {
+    pub fn fitness(&self) -> f32 {
+        let fitness =
+            10.0 * self.arrivals as f32
+            - 20.0 * self.collisions as f32
+            + self.average_speed
+            - 10.0 * self.average_deviation;
+        if fitness.is_nan() { f32::NEG_INFINITY } else { fitness }
+    }
+}
+
+fn alive_cars(car_system: &CarSystem) -> Vec<ecs::Entity<ForCar>> {
+    car_system.cars.iter()
+        .map(|(e, _)| *e)
+        .filter(|e| car_system.em.is_alive(*e))
+        .collect()
+}
+
+// Runs the cars of the map headless with a fixed time step, the street
+// lights draw their green durations from `seed`.
+pub fn simulate(fuzzy: CarFuzzy, config: &Config, steps: usize, seed: u64) -> Score {
+    let (mut road, mut car_system) = init::init(config);
+    car_system.set_fuzzy(fuzzy);
+    car_system.parallel = false;
+    let mut rng = SmallRng::seed_from_u64(seed);
+    road.reset_street_lights(config, &mut rng);
+
+    let mut score = Score::default();
+    let mut contacts = Vec::new();
+    let mut speed = 0.0;
+    let mut deviation = 0.0;
+    let mut samples = 0;
+    let mut deviation_samples = 0;
+
+    for _ in 0..steps {
+        road.step_street_lights(DT, config, &mut rng);
+        let before = alive_cars(&car_system);
+        car_system.step(DT, &road, config);
+        score.arrivals += before.iter()
+            .filter(|e| !car_system.em.is_alive(**e))
+            .count();
+
+        let fuzzy = &car_system.fuzzy;
+        let alive = alive_cars(&car_system);
+        for (i, &a) in alive.iter().enumerate() {
+            let car = car_system.cars.get(a);
+            speed += car.velocity;
+            samples += 1;
+
+            let value = fuzzy.input_value(&car.fuzzy_state, fuzzy.deviation.input);
+            if let Some(value) = value.ok().filter(|value| !value.is_nan()) {
+                deviation += f32::abs(value - 0.5);
+                deviation_samples += 1;
+            }
+
+            for &b in alive[i + 1..].iter() {
+                let distance = (car_system.cars.get(b).position - car.position).len();
+                let contact = contacts.iter().position(|&pair| pair == (a, b));
+                match (distance < config.car_width, contact) {
+                    (true, None) => {
+                        contacts.push((a, b));
+                        score.collisions += 1;
+                    },
+                    (false, Some(index)) => {
+                        contacts.swap_remove(index);
+                    },
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    if samples > 0 {
+        score.average_speed = speed / samples as f32;
+    }
+    if deviation_samples > 0 {
+        score.average_deviation = deviation / deviation_samples as f32;
+    }
+    score
+}
+
+fn gaussian<R: Rng>(rng: &mut R) -> f32 {
+    rng.sample::<f64, _>(StandardNormal) as f32
+}
+
+fn mutate<R: Rng>(
+    values: &mut [f32], ranges: &[(f32, f32)],
+    rate: f32, scale: f32, rng: &mut R)
+{
+    for (value, &(min, max)) in values.iter_mut().zip(ranges.iter()) {
+        if rng.gen::<f32>() < rate {
+            *value += gaussian(rng) * scale * (max - min);
+        }
+    }
+}
+
+fn crossover<R: Rng>(a: &[f32], b: &[f32], rng: &mut R) -> Vec<f32> {
+    a.iter().zip(b.iter())
+        .map(|(&x, &y)| if rng.gen::<bool>() { x } else { y })
+        .collect()
+}
+
+fn tournament<'a, R: Rng>(population: &'a [Candidate], rng: &mut R) -> &'a Candidate {
+    (0..TOURNAMENT_SIZE)
+        .map(|_| &population[rng.gen_range(0, population.len())])
+        .fold(None, |best: Option<&Candidate>, candidate| match best {
+            Some(best) if best.score.fitness() >= candidate.score.fitness() => Some(best),
+            _ => Some(candidate),
+        })
+        .unwrap()
+}
+
+fn evaluate(
+    genomes: Vec<Vec<f32>>, parameters: &[Parameter],
+    config: &Config, tuning: &Tuning)
+    -> Vec<Candidate>
+{
+    genomes.into_par_iter()
+        .map(|values| {
+            let mut fuzzy = CarFuzzy::new();
+            fuzzy.fuzzy.set_parameters(parameters, &values);
+            let values = parameters.iter().map(|&p| fuzzy.fuzzy.parameter(p)).collect();
+            let score = simulate(fuzzy, config, tuning.steps, tuning.seed);
+            Candidate { values, score }
+        })
+        .collect()
+}
+
+// A generational genetic algorithm over the membership parameters and rule
+// weights of `CarFuzzy`, starting from the hand-written values. All
+// randomness comes from `tuning.seed`, so runs are reproducible.
+pub fn tune(config: &Config, tuning: &Tuning) -> TuningReport {
+    let base = CarFuzzy::new();
+    let parameters = base.fuzzy.parameters();
+    let ranges: Vec<(f32, f32)> = parameters.iter()
+        .map(|&p| base.fuzzy.parameter_range(p))
+        .collect();
+    let initial: Vec<f32> = parameters.iter()
+        .map(|&p| base.fuzzy.parameter(p))
+        .collect();
+
+    let mut rng = SmallRng::seed_from_u64(tuning.seed);
+    let mut genomes = vec![initial.clone()];
+    while genomes.len() < tuning.population {
+        let mut values = initial.clone();
+        mutate(&mut values, &ranges, 1.0, tuning.mutation_scale, &mut rng);
+        genomes.push(values);
+    }
+
+    let mut population = evaluate(genomes, &parameters, config, tuning);
+    let mut history = Vec::new();
+    for generation in 0..tuning.generations.max(1) {
+        population.sort_by(|a, b| {
+            b.score.fitness().partial_cmp(&a.score.fitness()).unwrap()
+        });
+        history.push(population[0].score.fitness());
+        if generation + 1 >= tuning.generations {
+            break;
+        }
+
+        let elites = tuning.elites.clamp(1, population.len());
+        let mut children = Vec::new();
+        while children.len() + elites < tuning.population {
+            let a = tournament(&population, &mut rng);
+            let b = tournament(&population, &mut rng);
+            let mut child = crossover(&a.values, &b.values, &mut rng);
+            mutate(&mut child, &ranges, tuning.mutation_rate, tuning.mutation_scale, &mut rng);
+            children.push(child);
+        }
+        population.truncate(elites);
+        population.extend(evaluate(children, &parameters, config, tuning));
+    }
+
+    TuningReport {
+        parameters,
+        best: population.swap_remove(0),
+        history,
+    }
+}
+
+impl TuningReport {
+    pub fn fuzzy(&self) -> CarFuzzy {
+        let mut fuzzy = CarFuzzy::new();
+        fuzzy.fuzzy.set_parameters(&self.parameters, &self.best.values);
+        fuzzy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tune() {
+        let config = Config::new();
+        let tuning = Tuning {
+            seed: 7,
+            population: 4,
+            generations: 3,
+            steps: 120,
+            ..Tuning::default()
+        };
+
+        let baseline = simulate(CarFuzzy::new(), &config, tuning.steps, tuning.seed);
+        assert!(baseline.average_speed > 0.0);
+
+        let report = tune(&config, &tuning);
+        assert_eq!(report.history.len(), 3);
+        assert!(report.history.windows(2).all(|w| w[1] >= w[0]));
+        assert!(report.history[0] >= baseline.fitness());
+        assert_eq!(report.best.score.fitness(), report.history[2]);
+        assert_eq!(tune(&config, &tuning), report);
+
+        let fuzzy = CarFuzzy::from_json(&report.fuzzy().fuzzy.to_json()).unwrap();
+        assert_eq!(simulate(fuzzy, &config, tuning.steps, tuning.seed), report.best.score);
+    }
+}
@@ -0,0 +1,356 @@
+use crate::car::fuzzy::CarFuzzy;
+use crate::car::tuning::{self, Tuning};
+use crate::config::Config;
+use crate::fuzzy::{Fuzzy, ColorMap, Defuzzifier, Training, Clustering, ClusterMethod};
+
+const USAGE: &str = "usage: fuzzy [analyze [threshold] [samples]]
+       fuzzy surface <rule_set> <x_input> <y_input> <output> \
+[samples] [gray|heat] [path] [input=value...]
+       fuzzy train <samples.csv> <rule_set> <output> [epochs] [path]
+       fuzzy tune [generations] [population] [seed] [steps] [path]
+       fuzzy extract <samples.csv> <input,...> <output,...>
+       fuzzy cluster <samples.csv> <input,...> [clusters] [cmeans|gk]
+       fuzzy export [path] [mamdani|sugeno|interval] [uncertainty]
+       fuzzy evaluate <controller.json|controller.fcl> <rule_set|chain> <output,...> \
+[trace] [input=value...]
+       fuzzy simulate <controller.json> [steps] [seed] \
+[centroid|bisector|mom|som|lom|weighted] [resolution]";
+
+fn parse_arg<T: std::str::FromStr>(args: &[String], index: usize, default: T) -> Option<T> {
+    match args.get(index) {
+        Some(arg) => arg.parse().ok(),
+        None => Some(default),
+    }
+}
+
+fn analyze(args: &[String]) -> Result<(), String> {
+    let threshold = parse_arg(args, 0, 0.1).ok_or("threshold should be a number")?;
+    let samples = parse_arg(args, 1, 5).ok_or("samples should be an integer")?;
+
+    let car_fuzzy = CarFuzzy::new();
+    let fuzzy = &car_fuzzy.fuzzy;
+    let analysis = fuzzy.analyze(threshold, samples).map_err(|err| err.to_string())?;
+    print!("{}", fuzzy.format_analysis(&analysis));
+    Ok(())
+}
+
+fn fixed_inputs(fuzzy: &Fuzzy, args: &[String]) -> Result<Vec<f32>, String> {
+    let mut inputs = fuzzy.input_values();
+    for arg in args.iter().filter(|arg| arg.contains('=')) {
+        let mut parts = arg.splitn(2, '=');
+        let name = parts.next().unwrap();
+        let input = fuzzy.find_input(name)
+            .ok_or_else(|| format!("unknown input `{}`", name))?;
+        let value = parts.next().unwrap().parse()
+            .map_err(|_| format!("value of `{}` should be a number", name))?;
+        fuzzy.set_input_value(&mut inputs, input, value)
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(inputs)
+}
+
+fn surface(args: &[String]) -> Result<(), String> {
+    let car_fuzzy = CarFuzzy::new();
+    let fuzzy = &car_fuzzy.fuzzy;
+    let inputs = fixed_inputs(fuzzy, args)?;
+    let args: Vec<String> = args.iter()
+        .filter(|arg| !arg.contains('='))
+        .cloned()
+        .collect();
+    if args.len() < 4 {
+        return Err(USAGE.to_string());
+    }
+
+    let rule_set = fuzzy.find_rule_set(&args[0])
+        .ok_or_else(|| format!("unknown rule set `{}`", args[0]))?;
+    let x = fuzzy.find_input(&args[1])
+        .ok_or_else(|| format!("unknown input `{}`", args[1]))?;
+    let y = fuzzy.find_input(&args[2])
+        .ok_or_else(|| format!("unknown input `{}`", args[2]))?;
+    let output = fuzzy.find_output(&args[3])
+        .ok_or_else(|| format!("unknown output `{}`", args[3]))?;
+    let samples = parse_arg(&args, 4, 101).ok_or("samples should be an integer")?;
+    let color_map = match args.get(5).map(String::as_str) {
+        Some("gray") => ColorMap::Grayscale,
+        Some("heat") | None => ColorMap::Heat,
+        Some(other) => return Err(format!("unknown color map `{}`", other)),
+    };
+    let path = args.get(6).cloned().unwrap_or_else(|| "surface".to_string());
+
+    let surface = fuzzy.control_surface(rule_set, output, (x, y), &inputs, samples)
+        .map_err(|err| err.to_string())?;
+    std::fs::write(format!("{}.csv", path), fuzzy.surface_csv(&surface))
+        .map_err(|err| err.to_string())?;
+    surface.save_image(format!("{}.png", path), color_map)
+        .map_err(|err| err.to_string())?;
+    println!("wrote {0}.csv and {0}.png", path);
+    Ok(())
+}
+
+fn train(args: &[String]) -> Result<(), String> {
+    if args.len() < 3 {
+        return Err(USAGE.to_string());
+    }
+
+    let mut car_fuzzy = CarFuzzy::new();
+    let fuzzy = &mut car_fuzzy.fuzzy;
+    let csv = std::fs::read_to_string(&args[0])
+        .map_err(|err| format!("{}: {}", args[0], err))?;
+    let samples = fuzzy.read_samples(&csv)
+        .map_err(|err| format!("{}:{}", args[0], err))?;
+    let rule_set = fuzzy.find_rule_set(&args[1])
+        .ok_or_else(|| format!("unknown rule set `{}`", args[1]))?;
+    let output = fuzzy.find_output(&args[2])
+        .ok_or_else(|| format!("unknown output `{}`", args[2]))?;
+    let training = Training {
+        epochs: parse_arg(args, 3, 50).ok_or("epochs should be an integer")?,
+        ..Training::default()
+    };
+    let path = args.get(4).cloned().unwrap_or_else(|| "trained.fcl".to_string());
+
+    let report = fuzzy.train(rule_set, output, &samples, training)
+        .map_err(|err| err.to_string())?;
+    for (epoch, error) in report.errors.iter().enumerate() {
+        println!("epoch {}: rmse {}", epoch + 1, error);
+    }
+    std::fs::write(&path, fuzzy.to_fcl("car"))
+        .map_err(|err| err.to_string())?;
+    println!("wrote {}", path);
+    Ok(())
+}
+
+fn tune(args: &[String]) -> Result<(), String> {
+    let defaults = Tuning::default();
+    let tuning = Tuning {
+        generations: parse_arg(args, 0, defaults.generations)
+            .ok_or("generations should be an integer")?,
+        population: parse_arg(args, 1, defaults.population)
+            .ok_or("population should be an integer")?,
+        seed: parse_arg(args, 2, defaults.seed).ok_or("seed should be an integer")?,
+        steps: parse_arg(args, 3, defaults.steps).ok_or("steps should be an integer")?,
+        ..defaults
+    };
+    if tuning.population < 2 {
+        return Err("population should be at least 2".to_string());
+    }
+    let path = args.get(4).cloned().unwrap_or_else(|| "tuned.json".to_string());
+
+    let config = Config::new();
+    let baseline = tuning::simulate(CarFuzzy::new(), &config, tuning.steps, tuning.seed);
+    println!("baseline: fitness {} {:?}", baseline.fitness(), baseline);
+
+    let report = tuning::tune(&config, &tuning);
+    for (generation, fitness) in report.history.iter().enumerate() {
+        println!("generation {}: best fitness {}", generation + 1, fitness);
+    }
+    println!("best: {:?}", report.best.score);
+
+    let fuzzy = report.fuzzy().fuzzy;
+    print!("{}", fuzzy.format_parameters());
+    std::fs::write(&path, fuzzy.to_json())
+        .map_err(|err| err.to_string())?;
+    println!("wrote {}", path);
+    Ok(())
+}
+
+fn extract(args: &[String]) -> Result<(), String> {
+    if args.len() < 3 {
+        return Err(USAGE.to_string());
+    }
+
+    let mut car_fuzzy = CarFuzzy::new();
+    let fuzzy = &mut car_fuzzy.fuzzy;
+    let csv = std::fs::read_to_string(&args[0])
+        .map_err(|err| format!("{}: {}", args[0], err))?;
+    let samples = fuzzy.read_samples(&csv)
+        .map_err(|err| format!("{}:{}", args[0], err))?;
+    let inputs = args[1].split(',')
+        .map(|name| fuzzy.find_input(name).ok_or_else(|| format!("unknown input `{}`", name)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let outputs = args[2].split(',')
+        .map(|name| fuzzy.find_output(name).ok_or_else(|| format!("unknown output `{}`", name)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let extraction = fuzzy.extract_rules(&inputs, &outputs, &samples)
+        .map_err(|err| err.to_string())?;
+    print!("{}", fuzzy.format_extraction(&extraction));
+    Ok(())
+}
+
+fn cluster(args: &[String]) -> Result<(), String> {
+    if args.len() < 2 {
+        return Err(USAGE.to_string());
+    }
+
+    let mut car_fuzzy = CarFuzzy::new();
+    let fuzzy = &mut car_fuzzy.fuzzy;
+    let csv = std::fs::read_to_string(&args[0])
+        .map_err(|err| format!("{}: {}", args[0], err))?;
+    let samples = fuzzy.read_samples(&csv)
+        .map_err(|err| format!("{}:{}", args[0], err))?;
+    let inputs = args[1].split(',')
+        .map(|name| fuzzy.find_input(name).ok_or_else(|| format!("unknown input `{}`", name)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let method = match args.get(3).map(|arg| arg.as_str()) {
+        None | Some("cmeans") => ClusterMethod::CMeans,
+        Some("gk") => ClusterMethod::GustafsonKessel,
+        Some(method) => return Err(format!("unknown clustering method `{}`", method)),
+    };
+    let clustering = Clustering {
+        method,
+        clusters: parse_arg(args, 2, 3).ok_or("clusters should be an integer")?,
+        ..Clustering::default()
+    };
+
+    let sets = fuzzy.add_clustered_sets(&inputs, &samples, &clustering)
+        .map_err(|err| err.to_string())?;
+    for id in sets.into_iter().flatten() {
+        println!("{} = {:?}", fuzzy.input_set_name(id), fuzzy.input_set_function(id));
+    }
+    Ok(())
+}
+
+fn export(args: &[String]) -> Result<(), String> {
+    let path = args.first().cloned().unwrap_or_else(|| "car.json".to_string());
+    let car_fuzzy = match args.get(1).map(String::as_str) {
+        None | Some("mamdani") => CarFuzzy::new(),
+        Some("sugeno") => CarFuzzy::with_sugeno_steering(),
+        Some("interval") => {
+            let uncertainty: f32 = parse_arg(args, 2, 2.0).ok_or("uncertainty should be a number")?;
+            if uncertainty.is_nan() || uncertainty <= 0.0 {
+                return Err("uncertainty should be positive".to_string());
+            }
+            CarFuzzy::with_distance_uncertainty(uncertainty)
+        },
+        Some(other) => return Err(format!("unknown controller `{}`", other)),
+    };
+    std::fs::write(&path, car_fuzzy.fuzzy.to_json())
+        .map_err(|err| err.to_string())?;
+    println!("wrote {}", path);
+    Ok(())
+}
+
+fn load_controller(path: &str) -> Result<Fuzzy, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| format!("{}: {}", path, err))?;
+    let fuzzy =
+        if path.ends_with(".fcl") {
+            Fuzzy::from_fcl(&text)
+        }
+        else {
+            Fuzzy::from_json(&text)
+        };
+    fuzzy.map_err(|err| format!("{}:{}", path, err))
+}
+
+fn evaluate(args: &[String]) -> Result<(), String> {
+    let inputs: Vec<&String> = args.iter().filter(|arg| arg.contains('=')).collect();
+    let args: Vec<String> = args.iter()
+        .filter(|arg| !arg.contains('='))
+        .cloned()
+        .collect();
+    if args.len() < 3 {
+        return Err(USAGE.to_string());
+    }
+
+    let mut fuzzy = load_controller(&args[0])?;
+    for arg in inputs {
+        let mut parts = arg.splitn(2, '=');
+        let name = parts.next().unwrap();
+        let input = fuzzy.find_input(name)
+            .ok_or_else(|| format!("unknown input `{}`", name))?;
+        let value = parts.next().unwrap().parse()
+            .map_err(|_| format!("value of `{}` should be a number", name))?;
+        fuzzy.set_input(input, value).map_err(|err| err.to_string())?;
+    }
+    let outputs = args[2].split(',')
+        .map(|name| fuzzy.find_output(name).ok_or_else(|| format!("unknown output `{}`", name)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let trace = match args.get(3).map(String::as_str) {
+        None => false,
+        Some("trace") => true,
+        Some(other) => return Err(format!("unknown option `{}`", other)),
+    };
+
+    let evaluation =
+        if let Some(rule_set) = fuzzy.find_rule_set(&args[1]) {
+            fuzzy.evaluate(rule_set)
+        }
+        else if let Some(chain) = fuzzy.find_chain(&args[1]) {
+            fuzzy.evaluate_chain(chain)
+        }
+        else {
+            return Err(format!("unknown rule set or chain `{}`", args[1]));
+        };
+    let evaluation = evaluation.map_err(|err| err.to_string())?;
+
+    if trace {
+        if let Some(trace) = fuzzy.trace() {
+            print!("{}", trace);
+        }
+    }
+    for output in outputs {
+        match fuzzy.get_output(output) {
+            Ok(value) => match evaluation.interval(output) {
+                Some((lower, upper)) if lower < upper => println!(
+                    "{} = {} in [{}, {}]", fuzzy.output_name(output), value, lower, upper),
+                _ => println!("{} = {}", fuzzy.output_name(output), value),
+            },
+            Err(err) => println!("{}", err),
+        }
+    }
+    Ok(())
+}
+
+fn simulate(args: &[String]) -> Result<(), String> {
+    if args.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    let defaults = Tuning::default();
+    let steps = parse_arg(args, 1, defaults.steps).ok_or("steps should be an integer")?;
+    let seed = parse_arg(args, 2, defaults.seed).ok_or("seed should be an integer")?;
+    let text = std::fs::read_to_string(&args[0])
+        .map_err(|err| format!("{}: {}", args[0], err))?;
+    let mut fuzzy = CarFuzzy::from_json(&text)
+        .map_err(|err| format!("{}: {}", args[0], err))?;
+    let defuzzifier = match args.get(3).map(String::as_str) {
+        None => None,
+        Some("centroid") => Some(Defuzzifier::Centroid),
+        Some("bisector") => Some(Defuzzifier::Bisector),
+        Some("mom") => Some(Defuzzifier::MeanOfMaximum),
+        Some("som") => Some(Defuzzifier::SmallestOfMaximum),
+        Some("lom") => Some(Defuzzifier::LargestOfMaximum),
+        Some("weighted") => Some(Defuzzifier::WeightedAverage),
+        Some(other) => return Err(format!("unknown defuzzifier `{}`", other)),
+    };
+    if let Some(defuzzifier) = defuzzifier {
+        fuzzy.set_defuzzifier(defuzzifier);
+    }
+    if args.len() > 4 {
+        let resolution = parse_arg(args, 4, 0).ok_or("resolution should be an integer")?;
+        if resolution < 1 {
+            return Err("resolution should be at least 1".to_string());
+        }
+        fuzzy.set_resolution(resolution);
+    }
+
+    let score = tuning::simulate(fuzzy, &Config::new(), steps, seed);
+    println!("fitness {} {:?}", score.fitness(), score);
+    Ok(())
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    match args[0].as_str() {
+        "analyze" => analyze(&args[1..]),
+        "surface" => surface(&args[1..]),
+        "train" => train(&args[1..]),
+        "tune" => tune(&args[1..]),
+        "extract" => extract(&args[1..]),
+        "cluster" => cluster(&args[1..]),
+        "export" => export(&args[1..]),
+        "evaluate" => evaluate(&args[1..]),
+        "simulate" => simulate(&args[1..]),
+        command => Err(format!("unknown command `{}`\n{}", command, USAGE)),
+    }
+}
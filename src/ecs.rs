@@ -1,5 +1,7 @@
 use std::marker::PhantomData;
 
+use rayon::prelude::*;
+
 #[derive(Clone, Copy, Debug)]
 pub struct Entity<P> {
     index: usize, 
@@ -140,6 +142,19 @@ impl<T, P> Components<T, P> where T: Default, P: Copy {
     {
         self.entities.iter().zip(self.values.iter_mut())
     }
+
+    pub fn for_each_mut<F>(&mut self, parallel: bool, f: F)
+        where T: Send, P: Send + Sync, F: Fn(Entity<P>, &mut T) + Send + Sync
+    {
+        if parallel {
+            self.entities.par_iter().zip(self.values.par_iter_mut())
+                .for_each(|(e, v)| f(*e, v));
+        }
+        else {
+            self.entities.iter().zip(self.values.iter_mut())
+                .for_each(|(e, v)| f(*e, v));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -235,4 +250,20 @@ mod tests {
 
         assert_eq!(it.next(), None);
     }
+
+    #[test]
+    fn test_component_for_each_mut() {
+        let mut components = Components::<usize, TestComp>::new();
+        let mut em = EntityManager::<TestComp>::new();
+        for i in 0..100 {
+            let e = em.allocate();
+            components.set(e, i);
+        }
+
+        components.for_each_mut(true, |e, v| *v += e.index);
+        components.for_each_mut(false, |_, v| *v += 1);
+        for (e, v) in components.iter() {
+            assert_eq!(*v, 2 * e.index + 1);
+        }
+    }
 }
@@ -1,59 +1,151 @@
 mod math;
+mod shape;
+mod operators;
+mod antecedent;
+mod parser;
+mod fcl;
+mod trace;
+mod error;
+mod analysis;
+mod surface;
+mod data;
+mod anfis;
+mod parameters;
+mod wang_mendel;
+mod clustering;
+mod json;
+mod chain;
 
-type MembershipFunction = Box<dyn Fn(f32) -> f32>;
+use std::fmt;
 
-#[derive(Copy, Clone)]
+use serde::{Serialize, Deserialize};
+
+pub use self::shape::*;
+pub use self::operators::*;
+pub use self::antecedent::*;
+pub use self::parser::ParseError;
+pub use self::trace::*;
+pub use self::error::*;
+pub use self::surface::*;
+pub use self::data::*;
+pub use self::anfis::*;
+pub use self::parameters::*;
+pub use self::clustering::*;
+pub use self::math::{Scratch, Evaluation};
+
+const DEFAULT_RESOLUTION: usize = 40;
+
+pub enum MembershipFunction {
+    Shape(Shape),
+    Custom(Box<dyn Fn(f32) -> f32 + Send + Sync>),
+    Interval { lower: Shape, upper: Shape },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Consequent {
+    Constant(f32),
+    Linear {
+        coefficients: Vec<(InputId, f32)>,
+        constant: f32,
+    },
+}
+
+pub enum OutputTerm {
+    Membership(MembershipFunction),
+    Consequent(Consequent),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputKind {
+    Mamdani,
+    Sugeno,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Defuzzifier {
+    Centroid,
+    Bisector,
+    MeanOfMaximum,
+    SmallestOfMaximum,
+    LargestOfMaximum,
+    WeightedAverage,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct InputId { id: usize }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct OutputId { id: usize }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct InputSetId { id: usize }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct OutputSetId { id: usize }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct RuleId { id: usize }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct RuleSetId { id: usize }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ChainId { id: usize }
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Link {
+    pub output: OutputId,
+    pub input: InputId,
+}
+
 pub struct Input {
-    #[allow(dead_code)]
+    name: String,
     min: f32,
-    #[allow(dead_code)]
     max: f32,
+    range_policy: RangePolicy,
     value: f32,
 }
 
 pub struct Output {
+    name: String,
     min: f32,
     max: f32,
-    value: f32,
-    cached_output_sets: Vec<OutputSetId>,
+    kind: OutputKind,
+    defuzzifier: Defuzzifier,
+    resolution: usize,
+    fallback: Fallback,
 }
 
 pub struct InputSet {
+    name: String,
     input: InputId,
     f: MembershipFunction,
-    membership: f32,
 }
 
 pub struct OutputSet {
+    name: String,
     output: OutputId,
-    f: MembershipFunction,
-    input_membership: Option<f32>,
+    term: OutputTerm,
 }
 
 pub struct Rule {
-    input_sets: Vec<InputSetId>,
+    antecedent: Antecedent,
     output_set: OutputSetId,
+    weight: f32,
 }
 
 pub struct RuleSet {
+    name: String,
     rules: Vec<RuleId>,
+    operators: Operators,
+}
+
+pub struct Chain {
+    name: String,
+    rule_sets: Vec<RuleSetId>,
+    links: Vec<Link>,
 }
 
 pub struct Fuzzy {
@@ -63,14 +155,111 @@ pub struct Fuzzy {
     output_sets: Vec<OutputSet>,
     rules: Vec<Rule>,
     rule_sets: Vec<RuleSet>,
+    chains: Vec<Chain>,
+    scratch: Scratch,
+    evaluation: Evaluation,
 }
 
-impl OutputSet {
-    fn set_input_membership(&mut self, membership: f32) {
-        self.input_membership = match self.input_membership {
-            None => Some(membership),
-            Some(value) => Some(f32::max(membership, value)),
-        };
+impl MembershipFunction {
+    pub fn interval_around(shape: Shape, uncertainty: f32) -> Self {
+        MembershipFunction::Interval {
+            lower: shape.widened(-uncertainty),
+            upper: shape.widened(uncertainty),
+        }
+    }
+
+    // Interval sets are seen through their upper membership function
+    // wherever a single degree is needed.
+    pub fn eval(&self, x: f32) -> f32 {
+        match self {
+            MembershipFunction::Shape(shape) => shape.eval(x),
+            MembershipFunction::Custom(f) => f(x),
+            MembershipFunction::Interval { upper, .. } => upper.eval(x),
+        }
+    }
+
+    pub fn eval_interval(&self, x: f32) -> (f32, f32) {
+        match self {
+            MembershipFunction::Interval { lower, upper } => {
+                let upper = upper.eval(x);
+                (f32::min(lower.eval(x), upper), upper)
+            },
+            f => {
+                let membership = f.eval(x);
+                (membership, membership)
+            },
+        }
+    }
+
+    pub fn is_interval(&self) -> bool {
+        matches!(self, MembershipFunction::Interval { .. })
+    }
+
+    pub fn shape(&self) -> Option<&Shape> {
+        match self {
+            MembershipFunction::Shape(shape) => Some(shape),
+            MembershipFunction::Custom(_) | MembershipFunction::Interval { .. } => None,
+        }
+    }
+
+    // The shape whose parameters tuning and training may move. The points
+    // of a piecewise-linear shape are left alone, moving them one at a time
+    // could reorder them.
+    pub fn tunable_shape(&self) -> Option<&Shape> {
+        match self {
+            MembershipFunction::Shape(Shape::PiecewiseLinear(_)) => None,
+            MembershipFunction::Shape(shape) => Some(shape),
+            _ => None,
+        }
+    }
+
+    pub fn tunable_shape_mut(&mut self) -> Option<&mut Shape> {
+        match self {
+            MembershipFunction::Shape(Shape::PiecewiseLinear(_)) => None,
+            MembershipFunction::Shape(shape) => Some(shape),
+            _ => None,
+        }
+    }
+}
+
+impl OutputTerm {
+    pub fn membership(&self) -> Option<&MembershipFunction> {
+        match self {
+            OutputTerm::Membership(f) => Some(f),
+            OutputTerm::Consequent(_) => None,
+        }
+    }
+
+    pub fn consequent(&self) -> Option<&Consequent> {
+        match self {
+            OutputTerm::Membership(_) => None,
+            OutputTerm::Consequent(consequent) => Some(consequent),
+        }
+    }
+}
+
+impl From<Shape> for MembershipFunction {
+    fn from(shape: Shape) -> Self {
+        MembershipFunction::Shape(shape)
+    }
+}
+
+impl<F> From<Box<F>> for MembershipFunction
+    where F: Fn(f32) -> f32 + Send + Sync + 'static
+{
+    fn from(f: Box<F>) -> Self {
+        MembershipFunction::Custom(f)
+    }
+}
+
+impl std::fmt::Debug for MembershipFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MembershipFunction::Shape(shape) => shape.fmt(f),
+            MembershipFunction::Custom(_) => write!(f, "Custom"),
+            MembershipFunction::Interval { lower, upper } =>
+                write!(f, "Interval({:?}, {:?})", lower, upper),
+        }
     }
 }
 
@@ -83,75 +272,193 @@ impl Fuzzy {
             output_sets: Vec::new(),
             rules: Vec::new(),
             rule_sets: Vec::new(),
+            chains: Vec::new(),
+            scratch: Scratch::default(),
+            evaluation: Evaluation::default(),
         }
     }
 }
 
 impl Fuzzy {
     pub fn add_input(&mut self, min: f32, max: f32) -> InputId {
+        let id = self.inputs.len();
         let input = Input {
+            name: format!("input{}", id),
             min,
             max,
+            range_policy: RangePolicy::Accept,
             value: 0.0,
         };
-        let id = self.inputs.len();
         self.inputs.push(input);
         InputId { id }
     }
 
-    pub fn set_input(&mut self, id: InputId, value: f32) {
-        debug_assert!(id.id < self.inputs.len());
+    pub fn set_range_policy(
+        &mut self, id: InputId, range_policy: RangePolicy)
+        -> Result<(), FuzzyError>
+    {
+        let input = self.inputs.get_mut(id.id).ok_or(FuzzyError::UnknownInput(id))?;
+        input.range_policy = range_policy;
+        Ok(())
+    }
+
+    pub fn set_input(&mut self, id: InputId, value: f32) -> Result<(), FuzzyError> {
+        let value = self.check_input(id, value)?;
         self.inputs[id.id].value = value;
+        Ok(())
+    }
+
+    pub fn input_values(&self) -> Vec<f32> {
+        self.inputs.iter().map(|input| input.value).collect()
+    }
+
+    pub fn input_value(&self, inputs: &[f32], id: InputId) -> Result<f32, FuzzyError> {
+        let input = self.inputs.get(id.id).ok_or(FuzzyError::UnknownInput(id))?;
+        Ok(inputs.get(id.id).cloned().unwrap_or(input.value))
+    }
+
+    pub fn set_input_value(
+        &self, inputs: &mut [f32], id: InputId, value: f32)
+        -> Result<(), FuzzyError>
+    {
+        let value = self.check_input(id, value)?;
+        match inputs.get_mut(id.id) {
+            Some(input) => *input = value,
+            None => return Err(FuzzyError::UnknownInput(id)),
+        }
+        Ok(())
+    }
+
+    fn check_input(&self, id: InputId, value: f32) -> Result<f32, FuzzyError> {
+        let input = self.inputs.get(id.id)
+            .ok_or(FuzzyError::UnknownInput(id))?;
+
+        if input.range_policy != RangePolicy::Accept {
+            if value.is_nan() {
+                return Err(FuzzyError::NotANumber { input: input.name.clone() });
+            }
+            if (value < input.min || value > input.max)
+                && input.range_policy == RangePolicy::Reject
+            {
+                return Err(FuzzyError::OutOfRange {
+                    input: input.name.clone(),
+                    value,
+                    min: input.min,
+                    max: input.max,
+                });
+            }
+        }
+
+        if input.range_policy == RangePolicy::Clamp {
+            Ok(value.clamp(input.min, input.max))
+        }
+        else {
+            Ok(value)
+        }
     }
 }
 
 impl Fuzzy {
     pub fn add_output(&mut self, min: f32, max: f32) -> OutputId {
+        self.add_output_of_kind(min, max, OutputKind::Mamdani)
+    }
+
+    pub fn add_sugeno_output(&mut self, min: f32, max: f32) -> OutputId {
+        self.add_output_of_kind(min, max, OutputKind::Sugeno)
+    }
+
+    fn add_output_of_kind(
+        &mut self, min: f32, max: f32, kind: OutputKind)
+        -> OutputId
+    {
+        let id = self.outputs.len();
         let output = Output {
+            name: format!("output{}", id),
             min,
             max,
-            value: 0.0,
-            cached_output_sets: Vec::new(),
+            kind,
+            defuzzifier: Defuzzifier::Centroid,
+            resolution: DEFAULT_RESOLUTION,
+            fallback: Fallback::Undefined,
         };
-        let id = self.outputs.len();
         self.outputs.push(output);
         OutputId { id }
     }
 
-    pub fn get_output(&self, id: OutputId) -> f32 {
+    pub fn get_output(&self, id: OutputId) -> Result<f32, FuzzyError> {
+        let output = self.outputs.get(id.id).ok_or(FuzzyError::UnknownOutput(id))?;
+        self.evaluation.value(id)
+            .ok_or_else(|| FuzzyError::Undefined { output: output.name.clone() })
+    }
+
+    pub fn set_fallback(&mut self, id: OutputId, fallback: Fallback) {
+        debug_assert!(id.id < self.outputs.len());
+        self.outputs[id.id].fallback = fallback;
+    }
+
+    pub fn set_defuzzifier(&mut self, id: OutputId, defuzzifier: Defuzzifier) {
+        debug_assert!(id.id < self.outputs.len());
+        self.outputs[id.id].defuzzifier = defuzzifier;
+    }
+
+    pub fn set_resolution(&mut self, id: OutputId, resolution: usize) {
         debug_assert!(id.id < self.outputs.len());
-        self.outputs[id.id].value
+        debug_assert!(resolution > 0);
+        self.outputs[id.id].resolution = resolution;
     }
 }
 
 impl Fuzzy {
-    pub fn add_input_set(
-        &mut self, input: InputId, f: MembershipFunction) 
+    pub fn add_input_set<F>(
+        &mut self, input: InputId, f: F) 
         -> InputSetId 
+        where F: Into<MembershipFunction>
     {
+        let id = self.input_sets.len();
         let input_set = InputSet {
+            name: format!("term{}", id),
             input,
-            f,
-            membership: 0.0,
+            f: f.into(),
         };
 
-        let id = self.input_sets.len();
         self.input_sets.push(input_set);
         InputSetId { id }
     }
+
+    pub fn input_set_function(&self, id: InputSetId) -> &MembershipFunction {
+        debug_assert!(id.id < self.input_sets.len());
+        &self.input_sets[id.id].f
+    }
 }
 
 impl Fuzzy {
-    pub fn add_output_set(
-        &mut self, output: OutputId, f: MembershipFunction) 
+    pub fn add_output_set<F>(
+        &mut self, output: OutputId, f: F) 
         -> OutputSetId 
+        where F: Into<MembershipFunction>
+    {
+        debug_assert!(self.outputs[output.id].kind == OutputKind::Mamdani);
+        self.add_output_term(output, OutputTerm::Membership(f.into()))
+    }
+
+    pub fn add_consequent(
+        &mut self, output: OutputId, consequent: Consequent)
+        -> OutputSetId
+    {
+        debug_assert!(self.outputs[output.id].kind == OutputKind::Sugeno);
+        self.add_output_term(output, OutputTerm::Consequent(consequent))
+    }
+
+    fn add_output_term(
+        &mut self, output: OutputId, term: OutputTerm)
+        -> OutputSetId
     {
+        let id = self.output_sets.len();
         let output_set = OutputSet {
+            name: format!("term{}", id),
             output,
-            f, 
-            input_membership: None,
+            term,
         };
-        let id = self.output_sets.len();
         self.output_sets.push(output_set);
         OutputSetId { id }
     }
@@ -163,27 +470,312 @@ impl Fuzzy {
         output_set: OutputSetId) 
         -> RuleId 
     {
+        self.add_compound_rule(Antecedent::all(input_sets), output_set)
+    }
+
+    pub fn add_compound_rule(
+        &mut self, antecedent: Antecedent,
+        output_set: OutputSetId)
+        -> RuleId
+    {
+        self.add_weighted_compound_rule(antecedent, output_set, 1.0)
+    }
+
+    pub fn add_weighted_compound_rule(
+        &mut self, antecedent: Antecedent,
+        output_set: OutputSetId, weight: f32)
+        -> RuleId
+    {
+        debug_assert!((0.0..=1.0).contains(&weight));
         let rule = Rule {
-            input_sets: input_sets.to_vec(),
+            antecedent,
             output_set,
+            weight,
         };
         let id = self.rules.len();
         self.rules.push(rule);
         RuleId { id }
     }
+
+    // Adds one rule per alternative of the antecedent's ORs, so that each
+    // alternative fires on its own under every accumulation.
+    pub fn add_rules(
+        &mut self, antecedent: Antecedent,
+        output_set: OutputSetId)
+        -> Vec<RuleId>
+    {
+        antecedent.expand().into_iter()
+            .map(|antecedent| self.add_compound_rule(antecedent, output_set))
+            .collect()
+    }
+
+    pub fn rule_weight(&self, id: RuleId) -> f32 {
+        debug_assert!(id.id < self.rules.len());
+        self.rules[id.id].weight
+    }
+
+    pub fn set_rule_weight(&mut self, id: RuleId, weight: f32) {
+        debug_assert!(id.id < self.rules.len());
+        debug_assert!((0.0..=1.0).contains(&weight));
+        self.rules[id.id].weight = weight;
+    }
 }
 
 impl Fuzzy {
     pub fn add_rule_set(&mut self, rules: &[RuleId]) -> RuleSetId {
+        self.add_rule_set_with_operators(rules, Operators::default())
+    }
+
+    pub fn add_rule_set_with_operators(
+        &mut self, rules: &[RuleId], operators: Operators)
+        -> RuleSetId
+    {
+        let id = self.rule_sets.len();
         let rule_set = RuleSet {
+            name: format!("rules{}", id),
             rules: rules.to_vec(),
+            operators,
         };
-        let id = self.rule_sets.len();
         self.rule_sets.push(rule_set);
         RuleSetId { id }
     }
 }
 
+impl Fuzzy {
+    pub fn set_input_name(&mut self, id: InputId, name: &str) {
+        debug_assert!(id.id < self.inputs.len());
+        self.inputs[id.id].name = name.to_string();
+    }
+
+    pub fn set_output_name(&mut self, id: OutputId, name: &str) {
+        debug_assert!(id.id < self.outputs.len());
+        self.outputs[id.id].name = name.to_string();
+    }
+
+    pub fn set_input_set_name(&mut self, id: InputSetId, name: &str) {
+        debug_assert!(id.id < self.input_sets.len());
+        self.input_sets[id.id].name = name.to_string();
+    }
+
+    pub fn set_output_set_name(&mut self, id: OutputSetId, name: &str) {
+        debug_assert!(id.id < self.output_sets.len());
+        self.output_sets[id.id].name = name.to_string();
+    }
+
+    pub fn set_rule_set_name(&mut self, id: RuleSetId, name: &str) {
+        debug_assert!(id.id < self.rule_sets.len());
+        self.rule_sets[id.id].name = name.to_string();
+    }
+
+    pub fn set_chain_name(&mut self, id: ChainId, name: &str) {
+        debug_assert!(id.id < self.chains.len());
+        self.chains[id.id].name = name.to_string();
+    }
+
+    pub fn input_name(&self, id: InputId) -> &str {
+        &self.inputs[id.id].name
+    }
+
+    pub fn output_name(&self, id: OutputId) -> &str {
+        &self.outputs[id.id].name
+    }
+
+    pub fn input_set_name(&self, id: InputSetId) -> String {
+        let set = &self.input_sets[id.id];
+        format!("{}.{}", self.inputs[set.input.id].name, set.name)
+    }
+
+    pub fn output_set_name(&self, id: OutputSetId) -> String {
+        let set = &self.output_sets[id.id];
+        format!("{}.{}", self.outputs[set.output.id].name, set.name)
+    }
+
+    pub fn rule_set_name(&self, id: RuleSetId) -> &str {
+        &self.rule_sets[id.id].name
+    }
+
+    pub fn find_input(&self, name: &str) -> Option<InputId> {
+        self.inputs.iter()
+            .position(|input| input.name == name)
+            .map(|id| InputId { id })
+    }
+
+    pub fn find_output(&self, name: &str) -> Option<OutputId> {
+        self.outputs.iter()
+            .position(|output| output.name == name)
+            .map(|id| OutputId { id })
+    }
+
+    pub fn find_input_set(&self, input: InputId, name: &str) -> Option<InputSetId> {
+        self.input_sets.iter()
+            .position(|set| set.input == input && set.name == name)
+            .map(|id| InputSetId { id })
+    }
+
+    pub fn find_output_set(&self, output: OutputId, name: &str) -> Option<OutputSetId> {
+        self.output_sets.iter()
+            .position(|set| set.output == output && set.name == name)
+            .map(|id| OutputSetId { id })
+    }
+
+    pub fn find_rule_set(&self, name: &str) -> Option<RuleSetId> {
+        self.rule_sets.iter()
+            .position(|rule_set| rule_set.name == name)
+            .map(|id| RuleSetId { id })
+    }
+
+    pub fn find_chain(&self, name: &str) -> Option<ChainId> {
+        self.chains.iter()
+            .position(|chain| chain.name == name)
+            .map(|id| ChainId { id })
+    }
+
+    fn layout(&self) -> Vec<String> {
+        let inputs = self.inputs.iter().map(|input| format!("input `{}`", input.name));
+        let outputs = self.outputs.iter().map(|output| format!("output `{}`", output.name));
+        let input_sets = (0..self.input_sets.len())
+            .map(|id| format!("input set `{}`", self.input_set_name(InputSetId { id })));
+        let output_sets = (0..self.output_sets.len())
+            .map(|id| format!("output set `{}`", self.output_set_name(OutputSetId { id })));
+        let rule_sets = self.rule_sets.iter()
+            .map(|rule_set| format!("rule set `{}`", rule_set.name));
+        let chains = self.chains.iter().map(|chain| format!("chain `{}`", chain.name));
+        inputs.chain(outputs).chain(input_sets).chain(output_sets).chain(rule_sets).chain(chains)
+            .collect()
+    }
+
+    // Checks that other declares the same variables, sets and rule sets
+    // under the same ids, so that ids handed out by self are valid in it.
+    pub fn check_layout(&self, other: &Fuzzy) -> Result<(), FuzzyError> {
+        let expected = self.layout();
+        let found = other.layout();
+        for i in 0..usize::max(expected.len(), found.len()) {
+            if expected.get(i) != found.get(i) {
+                let describe = |item: Option<&String>| item.cloned()
+                    .unwrap_or_else(|| "nothing".to_string());
+                return Err(FuzzyError::Layout {
+                    expected: describe(expected.get(i)),
+                    found: describe(found.get(i)),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+struct DebugMap<K, V>(Vec<(K, V)>);
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for DebugMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.0.iter().map(|(k, v)| (k, v))).finish()
+    }
+}
+
+impl fmt::Debug for Fuzzy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let inputs = self.inputs.iter()
+            .map(|input| (&input.name, input.value))
+            .collect();
+        let input_sets = (0..self.input_sets.len())
+            .map(|id| {
+                let id = InputSetId { id };
+                (self.input_set_name(id), self.scratch.input_set_membership(id))
+            })
+            .collect();
+        let outputs = self.outputs.iter().enumerate()
+            .map(|(id, output)| (&output.name, self.evaluation.value(OutputId { id }).unwrap_or(f32::NAN)))
+            .collect();
+        let rule_sets = self.rule_sets.iter()
+            .map(|rule_set| {
+                let rules: Vec<String> = rule_set.rules.iter()
+                    .map(|&rule| self.format_rule(rule))
+                    .collect();
+                (&rule_set.name, rules)
+            })
+            .collect();
+
+        f.debug_struct("Fuzzy")
+            .field("inputs", &DebugMap(inputs))
+            .field("input_sets", &DebugMap(input_sets))
+            .field("outputs", &DebugMap(outputs))
+            .field("rule_sets", &DebugMap(rule_sets))
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        let mut fuzzy = Fuzzy::new();
+        let input = fuzzy.add_input(0.0, 1.0);
+        let near = fuzzy.add_input_set(input, Shape::LeftShoulder { a: 0.0, b: 1.0 });
+        let output = fuzzy.add_output(0.0, 1.0);
+        let slow = fuzzy.add_output_set(output, Shape::Triangle { a: 0.0, b: 0.5, c: 1.0 });
+        let rule = fuzzy.add_rule(&[near], slow);
+        let rule_set = fuzzy.add_rule_set(&[rule]);
+
+        assert_eq!(fuzzy.input_set_name(near), "input0.term0");
+
+        fuzzy.set_input_name(input, "car_distance");
+        fuzzy.set_input_set_name(near, "near");
+        fuzzy.set_output_name(output, "speed");
+        fuzzy.set_output_set_name(slow, "slow");
+        fuzzy.set_rule_set_name(rule_set, "normal");
+
+        assert_eq!(fuzzy.find_input("car_distance"), Some(input));
+        assert_eq!(fuzzy.find_input_set(input, "near"), Some(near));
+        assert_eq!(fuzzy.find_input_set(input, "far"), None);
+        assert_eq!(fuzzy.find_output_set(output, "slow"), Some(slow));
+        assert_eq!(fuzzy.find_rule_set("normal"), Some(rule_set));
+        assert_eq!(fuzzy.rule_set_name(rule_set), "normal");
+        assert_eq!(fuzzy.output_set_name(slow), "speed.slow");
+
+        fuzzy.set_input(input, 0.25).unwrap();
+        let debug = format!("{:?}", fuzzy);
+        assert!(debug.contains("\"car_distance\": 0.25"));
+        assert!(debug.contains("\"car_distance.near\""));
+        assert!(debug.contains("\"normal\": [\"IF car_distance IS near THEN speed IS slow\"]"));
+    }
+
+    #[test]
+    fn test_input_validation() {
+        let mut fuzzy = Fuzzy::new();
+        let input = fuzzy.add_input(0.0, 50.0);
+        fuzzy.set_input_name(input, "distance");
+
+        fuzzy.set_input(input, 1000.0).unwrap();
+        assert_eq!(fuzzy.inputs[input.id].value, 1000.0);
+
+        fuzzy.set_range_policy(input, RangePolicy::Clamp).unwrap();
+        fuzzy.set_input(input, 1000.0).unwrap();
+        assert_eq!(fuzzy.inputs[input.id].value, 50.0);
+        assert_eq!(
+            fuzzy.set_input(input, f32::NAN),
+            Err(FuzzyError::NotANumber { input: "distance".to_string() }));
+
+        fuzzy.set_range_policy(input, RangePolicy::Reject).unwrap();
+        let err = fuzzy.set_input(input, -1.0).unwrap_err();
+        assert_eq!(err.to_string(), "input `distance` = -1 is out of range [0, 50]");
+        assert_eq!(fuzzy.inputs[input.id].value, 50.0);
+
+        assert_eq!(
+            fuzzy.set_input(InputId { id: 1 }, 0.0),
+            Err(FuzzyError::UnknownInput(InputId { id: 1 })));
+        assert_eq!(
+            fuzzy.set_range_policy(InputId { id: 1 }, RangePolicy::Clamp),
+            Err(FuzzyError::UnknownInput(InputId { id: 1 })));
+        assert_eq!(fuzzy.input_value(&[], input), Ok(50.0));
+        assert_eq!(
+            fuzzy.input_value(&[], InputId { id: 1 }),
+            Err(FuzzyError::UnknownInput(InputId { id: 1 })));
+        assert_eq!(
+            fuzzy.get_output(OutputId { id: 0 }),
+            Err(FuzzyError::UnknownOutput(OutputId { id: 0 })));
+        assert_eq!(
+            fuzzy.evaluate(RuleSetId { id: 0 }),
+            Err(FuzzyError::UnknownRuleSet(RuleSetId { id: 0 })));
+    }
 }
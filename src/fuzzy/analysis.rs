@@ -0,0 +1,407 @@
+use std::fmt::Write;
+
+use super::*;
+use super::math::compute_input_membership;
+
+// Above this many grid points a rule set is sampled more coarsely.
+const MAX_GRID_POINTS: usize = 100_000;
+
+// The points of a rule set's sample grid where no rule fires at least at
+// the threshold, merged into the range they span on each input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverageGap {
+    pub rule_set: RuleSetId,
+    pub samples: usize,
+    pub points: usize,
+    pub count: usize,
+    pub ranges: Vec<(InputId, f32, f32)>,
+    pub weakest: Vec<(InputId, f32)>,
+    pub strength: f32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Analysis {
+    pub duplicates: Vec<(RuleId, RuleId)>,
+    pub conflicts: Vec<(RuleSetId, RuleId, RuleId)>,
+    pub unused_input_sets: Vec<InputSetId>,
+    pub unused_output_sets: Vec<OutputSetId>,
+    pub gaps: Vec<CoverageGap>,
+    pub threshold: f32,
+}
+
+fn canonical(antecedent: &Antecedent) -> Antecedent {
+    let flatten = |terms: &[Antecedent], is_and: bool| {
+        let mut result = Vec::new();
+        for term in terms.iter().map(canonical) {
+            match term {
+                Antecedent::And(inner) if is_and => result.extend(inner),
+                Antecedent::Or(inner) if !is_and => result.extend(inner),
+                term => result.push(term),
+            }
+        }
+        result.sort_by_key(|term| format!("{:?}", term));
+        result
+    };
+
+    match antecedent {
+        Antecedent::Is(id) => Antecedent::Is(*id),
+        Antecedent::And(terms) => {
+            let mut terms = flatten(terms, true);
+            if terms.len() == 1 { terms.remove(0) } else { Antecedent::And(terms) }
+        },
+        Antecedent::Or(terms) => {
+            let mut terms = flatten(terms, false);
+            if terms.len() == 1 { terms.remove(0) } else { Antecedent::Or(terms) }
+        },
+        Antecedent::Not(term) => Antecedent::Not(Box::new(canonical(term))),
+        Antecedent::Hedge(hedge, term) => Antecedent::Hedge(*hedge, Box::new(canonical(term))),
+    }
+}
+
+impl Fuzzy {
+    pub(super) fn used_rules(&self) -> Vec<RuleId> {
+        let mut rules: Vec<RuleId> = self.rule_sets.iter()
+            .flat_map(|rule_set| rule_set.rules.iter().cloned())
+            .collect();
+        rules.sort_by_key(|rule| rule.id);
+        rules.dedup();
+        rules
+    }
+
+    pub(super) fn rule_inputs(&self, rules: &[RuleId]) -> Vec<InputId> {
+        let mut input_sets = Vec::new();
+        for rule in rules.iter() {
+            self.rules[rule.id].antecedent.collect_input_sets(&mut input_sets);
+        }
+        let mut inputs: Vec<InputId> = input_sets.iter()
+            .map(|set| self.input_sets[set.id].input)
+            .collect();
+        inputs.sort_by_key(|input| input.id);
+        inputs.dedup();
+        inputs
+    }
+
+    pub fn duplicate_rules(&self) -> Vec<(RuleId, RuleId)> {
+        let rules = self.used_rules();
+        let antecedents: Vec<Antecedent> = rules.iter()
+            .map(|rule| canonical(&self.rules[rule.id].antecedent))
+            .collect();
+
+        let mut duplicates = Vec::new();
+        for (i, &rule) in rules.iter().enumerate() {
+            let original = (0..i).find(|&j| {
+                antecedents[j] == antecedents[i]
+                    && self.rules[rules[j].id].output_set == self.rules[rule.id].output_set
+            });
+            if let Some(j) = original {
+                duplicates.push((rules[j], rule));
+            }
+        }
+        duplicates
+    }
+
+    pub fn conflicting_rules(&self, rule_set: RuleSetId) -> Vec<(RuleId, RuleId)> {
+        let rules = &self.rule_sets[rule_set.id].rules;
+        let antecedents: Vec<Antecedent> = rules.iter()
+            .map(|rule| canonical(&self.rules[rule.id].antecedent))
+            .collect();
+
+        let mut conflicts = Vec::new();
+        for i in 0..rules.len() {
+            for j in 0..i {
+                let a = &self.output_sets[self.rules[rules[j].id].output_set.id];
+                let b = &self.output_sets[self.rules[rules[i].id].output_set.id];
+                if antecedents[j] == antecedents[i]
+                    && a.output == b.output
+                    && self.rules[rules[j].id].output_set != self.rules[rules[i].id].output_set
+                {
+                    conflicts.push((rules[j], rules[i]));
+                }
+            }
+        }
+        conflicts
+    }
+
+    pub fn unused_input_sets(&self) -> Vec<InputSetId> {
+        let mut used = Vec::new();
+        for rule in self.used_rules() {
+            self.rules[rule.id].antecedent.collect_input_sets(&mut used);
+        }
+        (0..self.input_sets.len())
+            .map(|id| InputSetId { id })
+            .filter(|id| !used.contains(id))
+            .collect()
+    }
+
+    pub fn unused_output_sets(&self) -> Vec<OutputSetId> {
+        let used: Vec<OutputSetId> = self.used_rules().iter()
+            .map(|rule| self.rules[rule.id].output_set)
+            .collect();
+        (0..self.output_sets.len())
+            .map(|id| OutputSetId { id })
+            .filter(|id| !used.contains(id))
+            .collect()
+    }
+
+    pub(super) fn sample_range(&self, id: InputId) -> (f32, f32) {
+        let input = &self.inputs[id.id];
+        if input.min.is_finite() && input.max.is_finite() {
+            return (input.min, input.max);
+        }
+
+        let xs: Vec<f32> = self.input_sets.iter()
+            .filter(|set| set.input == id)
+            .filter_map(|set| set.f.shape())
+            .flat_map(|shape| {
+                shape.linear_points()
+                    .map(|points| points.iter().map(|&(x, _)| x).collect())
+                    .or_else(|| shape.peak().map(|peak| vec![peak]))
+                    .unwrap_or_default()
+            })
+            .collect();
+        let min = xs.iter().cloned().fold(f32::INFINITY, f32::min);
+        let max = xs.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        if min <= max { (min, max) } else { (0.0, 0.0) }
+    }
+
+    // The number of samples per input, at most `samples`, that keeps the
+    // grid over `inputs` inputs within MAX_GRID_POINTS.
+    fn grid_samples(&self, rule_set: RuleSetId, inputs: usize, samples: usize)
+        -> Result<usize, FuzzyError>
+    {
+        if samples < 2 {
+            return Err(FuzzyError::TooFewSamples { samples });
+        }
+        let fits = |samples: usize| {
+            samples.checked_pow(inputs as u32).is_some_and(|points| points <= MAX_GRID_POINTS)
+        };
+        if !fits(2) {
+            return Err(FuzzyError::TooManyInputs {
+                rule_set: self.rule_sets[rule_set.id].name.clone(),
+                inputs,
+            });
+        }
+        Ok((2..=samples).rev().find(|&samples| fits(samples)).unwrap())
+    }
+
+    // Samples the inputs the rule set reads on a grid and returns None when
+    // some rule fires at least at the threshold everywhere.
+    pub fn coverage_gaps(
+        &self, rule_set: RuleSetId, threshold: f32, samples: usize)
+        -> Result<Option<CoverageGap>, FuzzyError>
+    {
+        if rule_set.id >= self.rule_sets.len() {
+            return Err(FuzzyError::UnknownRuleSet(rule_set));
+        }
+
+        let rules = &self.rule_sets[rule_set.id].rules;
+        let operators = self.rule_sets[rule_set.id].operators;
+        let inputs = self.rule_inputs(rules);
+        let samples = self.grid_samples(rule_set, inputs.len(), samples)?;
+        let grid: Vec<Vec<f32>> = inputs.iter()
+            .map(|&input| {
+                let (min, max) = self.sample_range(input);
+                (0..samples)
+                    .map(|i| min + i as f32 * (max - min) / (samples - 1) as f32)
+                    .collect()
+            })
+            .collect();
+        let input_sets: Vec<(usize, usize)> = self.input_sets.iter().enumerate()
+            .filter_map(|(id, set)| {
+                inputs.iter().position(|&input| input == set.input).map(|axis| (id, axis))
+            })
+            .collect();
+
+        let mut gap: Option<CoverageGap> = None;
+        let mut points = 0;
+        let mut memberships = vec![0.0; self.input_sets.len()];
+        let mut indices = vec![0; inputs.len()];
+
+        loop {
+            points += 1;
+            for &(id, axis) in input_sets.iter() {
+                memberships[id] = self.input_sets[id].f.eval(grid[axis][indices[axis]]);
+            }
+
+            let strength = rules.iter()
+                .map(|&rule| compute_input_membership(&self.rules, &memberships, rule, operators))
+                .fold(0.0, f32::max);
+            if strength < threshold {
+                let point = inputs.iter().zip(indices.iter())
+                    .enumerate()
+                    .map(|(axis, (&input, &i))| (input, grid[axis][i]));
+                let gap = gap.get_or_insert_with(|| CoverageGap {
+                    rule_set,
+                    samples,
+                    points: 0,
+                    count: 0,
+                    ranges: point.clone().map(|(input, x)| (input, x, x)).collect(),
+                    weakest: point.clone().collect(),
+                    strength,
+                });
+                gap.count += 1;
+                for ((_, min, max), (_, x)) in gap.ranges.iter_mut().zip(point.clone()) {
+                    *min = min.min(x);
+                    *max = max.max(x);
+                }
+                if strength < gap.strength {
+                    gap.weakest = point.collect();
+                    gap.strength = strength;
+                }
+            }
+
+            let next = indices.iter().position(|&i| i + 1 < samples);
+            match next {
+                Some(axis) => {
+                    indices[axis] += 1;
+                    for index in indices[..axis].iter_mut() {
+                        *index = 0;
+                    }
+                },
+                None => break,
+            }
+        }
+
+        if let Some(gap) = gap.as_mut() {
+            gap.points = points;
+        }
+        Ok(gap)
+    }
+
+    pub fn analyze(&self, threshold: f32, samples: usize) -> Result<Analysis, FuzzyError> {
+        let rule_sets: Vec<RuleSetId> = (0..self.rule_sets.len())
+            .map(|id| RuleSetId { id })
+            .collect();
+        let mut gaps = Vec::new();
+        for &rule_set in rule_sets.iter() {
+            gaps.extend(self.coverage_gaps(rule_set, threshold, samples)?);
+        }
+
+        Ok(Analysis {
+            duplicates: self.duplicate_rules(),
+            conflicts: rule_sets.iter()
+                .flat_map(|&rule_set| {
+                    self.conflicting_rules(rule_set).into_iter()
+                        .map(move |(a, b)| (rule_set, a, b))
+                })
+                .collect(),
+            unused_input_sets: self.unused_input_sets(),
+            unused_output_sets: self.unused_output_sets(),
+            gaps,
+            threshold,
+        })
+    }
+
+    pub fn format_analysis(&self, analysis: &Analysis) -> String {
+        let mut text = String::new();
+
+        if !analysis.duplicates.is_empty() {
+            text.push_str("duplicate rules:\n");
+            for &(original, duplicate) in analysis.duplicates.iter() {
+                writeln!(text, "    rule {} repeats rule {}: {}",
+                         duplicate.id, original.id, self.format_rule(duplicate)).unwrap();
+            }
+        }
+
+        if !analysis.conflicts.is_empty() {
+            text.push_str("conflicting rules:\n");
+            for &(rule_set, a, b) in analysis.conflicts.iter() {
+                writeln!(text, "    in {}:", self.rule_sets[rule_set.id].name).unwrap();
+                writeln!(text, "        {}", self.format_rule(a)).unwrap();
+                writeln!(text, "        {}", self.format_rule(b)).unwrap();
+            }
+        }
+
+        if !analysis.unused_input_sets.is_empty() {
+            let names: Vec<String> = analysis.unused_input_sets.iter()
+                .map(|&id| self.input_set_name(id))
+                .collect();
+            writeln!(text, "unused input sets: {}", names.join(", ")).unwrap();
+        }
+
+        if !analysis.unused_output_sets.is_empty() {
+            let names: Vec<String> = analysis.unused_output_sets.iter()
+                .map(|&id| self.output_set_name(id))
+                .collect();
+            writeln!(text, "unused output sets: {}", names.join(", ")).unwrap();
+        }
+
+        for gap in analysis.gaps.iter() {
+            let name = |input: InputId| &self.inputs[input.id].name;
+            writeln!(text, "coverage gaps in {} (strength < {}, {} samples per input): {} of {} points",
+                     self.rule_sets[gap.rule_set.id].name, analysis.threshold,
+                     gap.samples, gap.count, gap.points).unwrap();
+            let ranges: Vec<String> = gap.ranges.iter()
+                .map(|&(input, min, max)| format!("{} in [{}, {}]", name(input), min, max))
+                .collect();
+            writeln!(text, "    within {}", ranges.join(", ")).unwrap();
+            let weakest: Vec<String> = gap.weakest.iter()
+                .map(|&(input, x)| format!("{} = {}", name(input), x))
+                .collect();
+            writeln!(text, "    weakest {:.3} at {}", gap.strength, weakest.join(", ")).unwrap();
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analysis() {
+        let mut fuzzy = Fuzzy::new();
+        let light = fuzzy.add_input(0.0, 1.0);
+        let distance = fuzzy.add_input(0.0, 10.0);
+        let green = fuzzy.add_input_set(light, Shape::LeftShoulder { a: 0.0, b: 0.5 });
+        let red = fuzzy.add_input_set(light, Shape::RightShoulder { a: 0.5, b: 1.0 });
+        let near = fuzzy.add_input_set(distance, Shape::LeftShoulder { a: 0.0, b: 10.0 });
+        let far = fuzzy.add_input_set(distance, Shape::RightShoulder { a: 0.0, b: 10.0 });
+        let speed = fuzzy.add_output(0.0, 1.0);
+        let slow = fuzzy.add_output_set(speed, Shape::Triangle { a: 0.0, b: 0.25, c: 0.5 });
+        let fast = fuzzy.add_output_set(speed, Shape::Triangle { a: 0.5, b: 0.75, c: 1.0 });
+        let stop = fuzzy.add_output_set(speed, Shape::LeftShoulder { a: 0.0, b: 0.1 });
+        fuzzy.set_input_name(light, "light");
+        fuzzy.set_input_set_name(far, "far");
+        fuzzy.set_output_name(speed, "speed");
+        fuzzy.set_output_set_name(stop, "stop");
+
+        let rule1 = fuzzy.add_rule(&[green, near], fast);
+        let rule2 = fuzzy.add_compound_rule(
+            Antecedent::is(near).and(Antecedent::is(green)), fast);
+        let rule3 = fuzzy.add_rule(&[near, green], slow);
+        let rule4 = fuzzy.add_rule(&[red], slow);
+        let rule_set = fuzzy.add_rule_set(&[rule1, rule2, rule3, rule4]);
+        let other = fuzzy.add_rule_set(&[rule4]);
+
+        assert_eq!(fuzzy.duplicate_rules(), vec![(rule1, rule2)]);
+        assert_eq!(fuzzy.conflicting_rules(rule_set), vec![(rule1, rule3), (rule2, rule3)]);
+        assert!(fuzzy.conflicting_rules(other).is_empty());
+        assert_eq!(fuzzy.unused_input_sets(), vec![far]);
+        assert_eq!(fuzzy.unused_output_sets(), vec![stop]);
+
+        let gap = fuzzy.coverage_gaps(rule_set, 0.1, 3).unwrap().unwrap();
+        assert_eq!((gap.samples, gap.points, gap.count), (3, 9, 4));
+        assert_eq!(gap.ranges, vec![(light, 0.0, 0.5), (distance, 0.0, 10.0)]);
+        assert_eq!(gap.weakest, vec![(light, 0.5), (distance, 0.0)]);
+        assert_eq!(gap.strength, 0.0);
+        let gap = fuzzy.coverage_gaps(other, 0.1, 3).unwrap().unwrap();
+        assert_eq!((gap.points, gap.count), (3, 2));
+        assert_eq!(fuzzy.coverage_gaps(rule_set, 0.0, 3), Ok(None));
+
+        let gap = fuzzy.coverage_gaps(rule_set, 0.1, 1000).unwrap().unwrap();
+        assert_eq!((gap.samples, gap.points), (316, 316 * 316));
+        assert_eq!(fuzzy.coverage_gaps(rule_set, 0.1, 1),
+                   Err(FuzzyError::TooFewSamples { samples: 1 }));
+
+        let analysis = fuzzy.analyze(0.1, 3).unwrap();
+        assert_eq!(analysis.gaps.len(), 2);
+        let text = fuzzy.format_analysis(&analysis);
+        assert!(text.contains("unused input sets: input1.far\n"));
+        assert!(text.contains("unused output sets: speed.stop\n"));
+        assert!(text.contains(
+            "coverage gaps in rules0 (strength < 0.1, 3 samples per input): 4 of 9 points\n"));
+        assert!(text.contains("    within light in [0, 0.5], input1 in [0, 10]\n"));
+    }
+}
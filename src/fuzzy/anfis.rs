@@ -0,0 +1,305 @@
+use nalgebra as na;
+
+use super::*;
+use super::math::compute_input_membership;
+
+const SVD_EPSILON: f64 = 1e-9;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Training {
+    pub epochs: usize,
+    pub step_size: f32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrainingReport {
+    pub errors: Vec<f32>,
+    pub consequents: Vec<OutputSetId>,
+    pub premises: Vec<InputSetId>,
+}
+
+struct Problem<'a> {
+    rule_set: RuleSetId,
+    output: OutputId,
+    regressors: Vec<InputId>,
+    consequents: Vec<OutputSetId>,
+    samples: &'a [Sample],
+}
+
+impl Default for Training {
+    fn default() -> Self {
+        Training {
+            epochs: 50,
+            step_size: 0.01,
+        }
+    }
+}
+
+impl Fuzzy {
+    fn tunable_shape(&mut self, id: InputSetId) -> Option<&mut Shape> {
+        self.input_sets[id.id].f.tunable_shape_mut()
+    }
+
+    fn firing_row(
+        &self, problem: &Problem, sample: &Sample, scratch: &mut Scratch)
+        -> Result<Option<Vec<f64>>, FuzzyError>
+    {
+        self.evaluate_with(problem.rule_set, &sample.inputs, scratch)?;
+        let rule_set = &self.rule_sets[problem.rule_set.id];
+
+        let mut weights = vec![0.0; problem.consequents.len()];
+        for rule in rule_set.rules.iter() {
+            let output_set = self.rules[rule.id].output_set;
+            if let Some(k) = problem.consequents.iter().position(|&id| id == output_set) {
+                weights[k] += compute_input_membership(
+                    &self.rules, scratch.input_memberships(), *rule, rule_set.operators) as f64;
+            }
+        }
+
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return Ok(None);
+        }
+
+        let mut row = Vec::with_capacity(weights.len() * (problem.regressors.len() + 1));
+        for weight in weights {
+            let weight = weight / total;
+            row.push(weight);
+            for &input in problem.regressors.iter() {
+                row.push(weight * scratch.input_value(input) as f64);
+            }
+        }
+        Ok(Some(row))
+    }
+
+    fn fit_consequents(&mut self, problem: &Problem) -> Result<(), FuzzyError> {
+        let mut scratch = Scratch::default();
+        let mut rows = Vec::new();
+        let mut targets = Vec::new();
+        for sample in problem.samples.iter() {
+            if let Some(target) = sample.output(problem.output) {
+                if let Some(row) = self.firing_row(problem, sample, &mut scratch)? {
+                    rows.extend(row);
+                    targets.push(target as f64);
+                }
+            }
+        }
+        if targets.is_empty() {
+            return Err(FuzzyError::NoSamples);
+        }
+
+        let columns = rows.len() / targets.len();
+        let a = na::DMatrix::from_row_slice(targets.len(), columns, &rows);
+        let b = na::DVector::from_vec(targets);
+        let theta = a.svd(true, true).solve(&b, SVD_EPSILON)
+            .expect("SVD with both U and V should be solvable");
+
+        let width = problem.regressors.len() + 1;
+        for (k, id) in problem.consequents.iter().enumerate() {
+            let params = &theta.as_slice()[k * width..(k + 1) * width];
+            let consequent = Consequent::Linear {
+                coefficients: problem.regressors.iter()
+                    .zip(params[1..].iter())
+                    .map(|(&input, &coefficient)| (input, coefficient as f32))
+                    .collect(),
+                constant: params[0] as f32,
+            };
+            self.output_sets[id.id].term = OutputTerm::Consequent(consequent);
+        }
+        Ok(())
+    }
+
+    fn squared_error(&self, problem: &Problem) -> Result<(f64, usize), FuzzyError> {
+        let mut scratch = Scratch::default();
+        let mut sum = 0.0;
+        let mut count = 0;
+        for sample in problem.samples.iter() {
+            if let Some(target) = sample.output(problem.output) {
+                let evaluation = self.evaluate_with(problem.rule_set, &sample.inputs, &mut scratch)?;
+                if evaluation.fell_back(problem.output) {
+                    continue;
+                }
+                if let Some(value) = evaluation.value(problem.output) {
+                    sum += (value as f64 - target as f64).powi(2);
+                    count += 1;
+                }
+            }
+        }
+        Ok((sum, count))
+    }
+
+    fn descend_premises(&mut self, problem: &Problem, premises: &[InputSetId], step: f32)
+        -> Result<(), FuzzyError>
+    {
+        let mut gradient = Vec::new();
+        for &id in premises.iter() {
+            let scale = {
+                let (min, max) = self.sample_range(self.input_sets[id.id].input);
+                f32::max(max - min, f32::EPSILON)
+            };
+            let params = self.tunable_shape(id).unwrap().parameters();
+            for (k, &value) in params.iter().enumerate() {
+                let h = 1e-3 * scale;
+                // Probes a repaired shape, a step past a neighbouring
+                // parameter would otherwise leave it unordered.
+                let mut error_at = |x: f32| {
+                    let mut moved = params.clone();
+                    moved[k] = x;
+                    let shape = self.tunable_shape(id).unwrap();
+                    shape.set_parameters(&moved);
+                    shape.repair();
+                    self.squared_error(problem).map(|(sum, _)| sum)
+                };
+                let errors = error_at(value + h)
+                    .and_then(|above| Ok((above, error_at(value - h)?)));
+                self.tunable_shape(id).unwrap().set_parameters(&params);
+                let (above, below) = errors?;
+                let derivative = (above - below) / (2.0 * h as f64);
+                gradient.push((id, k, scale, derivative as f32 * scale));
+            }
+        }
+
+        let norm = gradient.iter().map(|&(_, _, _, g)| g * g).sum::<f32>().sqrt();
+        if norm.is_nan() || norm <= 0.0 {
+            return Ok(());
+        }
+        for &(id, k, scale, g) in gradient.iter() {
+            let shape = self.tunable_shape(id).unwrap();
+            let mut params = shape.parameters();
+            params[k] -= step * scale * g / norm;
+            shape.set_parameters(&params);
+        }
+        for &id in premises.iter() {
+            self.tunable_shape(id).unwrap().repair();
+        }
+        Ok(())
+    }
+
+    // Hybrid learning: each epoch fits the linear consequents by least
+    // squares with the premises fixed, then takes a normalized gradient
+    // step on the premise shape parameters with the consequents fixed.
+    pub fn train(
+        &mut self, rule_set: RuleSetId, output: OutputId,
+        samples: &[Sample], training: Training)
+        -> Result<TrainingReport, FuzzyError>
+    {
+        if rule_set.id >= self.rule_sets.len() {
+            return Err(FuzzyError::UnknownRuleSet(rule_set));
+        }
+        match self.outputs.get(output.id) {
+            None => return Err(FuzzyError::UnknownOutput(output)),
+            Some(o) if o.kind != OutputKind::Sugeno =>
+                return Err(FuzzyError::NotSugeno { output: o.name.clone() }),
+            Some(_) => (),
+        }
+
+        let rules = self.rule_sets[rule_set.id].rules.clone();
+        let mut consequents: Vec<OutputSetId> = rules.iter()
+            .map(|rule| self.rules[rule.id].output_set)
+            .filter(|set| self.output_sets[set.id].output == output)
+            .collect();
+        consequents.sort_by_key(|set| set.id);
+        consequents.dedup();
+
+        let mut premises = Vec::new();
+        for rule in rules.iter() {
+            self.rules[rule.id].antecedent.collect_input_sets(&mut premises);
+        }
+        premises.sort_by_key(|set| set.id);
+        premises.dedup();
+        premises.retain(|&id| self.tunable_shape(id).is_some());
+
+        let problem = Problem {
+            rule_set,
+            output,
+            regressors: self.rule_inputs(&rules),
+            consequents: consequents.clone(),
+            samples,
+        };
+
+        let mut report = TrainingReport {
+            errors: Vec::new(),
+            consequents,
+            premises: premises.clone(),
+        };
+        let mut step = training.step_size;
+        let mut decreases = 0;
+        for epoch in 0..training.epochs.max(1) {
+            self.fit_consequents(&problem)?;
+            let (sum, count) = self.squared_error(&problem)?;
+            let error = (sum / count.max(1) as f64).sqrt() as f32;
+
+            // Jang's step size heuristics.
+            if let Some(&previous) = report.errors.last() {
+                if error < previous {
+                    decreases += 1;
+                    if decreases == 4 {
+                        step *= 1.1;
+                        decreases = 0;
+                    }
+                }
+                else {
+                    step *= 0.9;
+                    decreases = 0;
+                }
+            }
+            report.errors.push(error);
+
+            if epoch + 1 < training.epochs {
+                self.descend_premises(&problem, &premises, step)?;
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_train() {
+        let mut fuzzy = Fuzzy::new();
+        let x = fuzzy.add_input(0.0, 1.0);
+        let y = fuzzy.add_sugeno_output(-1.0, 2.0);
+        let mamdani = fuzzy.add_output(0.0, 1.0);
+        let low = fuzzy.add_input_set(x, Shape::LeftShoulder { a: 0.2, b: 0.9 });
+        let high = fuzzy.add_input_set(x, Shape::RightShoulder { a: 0.1, b: 0.8 });
+        let y_low = fuzzy.add_consequent(y, Consequent::Constant(0.0));
+        let y_high = fuzzy.add_consequent(y, Consequent::Constant(0.0));
+        let r1 = fuzzy.add_rule(&[low], y_low);
+        let r2 = fuzzy.add_rule(&[high], y_high);
+        let rule_set = fuzzy.add_rule_set(&[r1, r2]);
+
+        let target = |x: f32| if x < 0.5 { 0.2 } else { 1.0 - x * x };
+        let samples: Vec<Sample> = (0..=40)
+            .map(|i| {
+                let value = i as f32 / 40.0;
+                Sample { inputs: vec![value], outputs: vec![target(value), f32::NAN] }
+            })
+            .collect();
+
+        let report = fuzzy.train(rule_set, y, &samples, Training::default()).unwrap();
+        assert_eq!(report.errors.len(), 50);
+        assert_eq!(report.consequents, vec![y_low, y_high]);
+        assert_eq!(report.premises, vec![low, high]);
+        assert!(report.errors[49] < report.errors[0] * 0.8);
+        assert!(fuzzy.input_set_function(low).shape().unwrap() != &Shape::LeftShoulder { a: 0.2, b: 0.9 });
+
+        let mut scratch = Scratch::default();
+        let evaluation = fuzzy.evaluate_with(rule_set, &[0.9], &mut scratch).unwrap();
+        assert_relative_eq!(evaluation.value(y).unwrap(), target(0.9), epsilon = 0.1);
+
+        let loaded = Fuzzy::from_fcl(&fuzzy.to_fcl("learned")).unwrap();
+        let reloaded = loaded.evaluate_with(rule_set, &[0.9], &mut scratch).unwrap();
+        assert_relative_eq!(reloaded.value(y).unwrap(), evaluation.value(y).unwrap(), epsilon = 1e-4);
+
+        assert_eq!(
+            fuzzy.train(rule_set, mamdani, &samples, Training::default()),
+            Err(FuzzyError::NotSugeno { output: "output1".to_string() }));
+        assert_eq!(
+            fuzzy.train(rule_set, y, &samples[..0], Training::default()),
+            Err(FuzzyError::NoSamples));
+    }
+}
@@ -0,0 +1,179 @@
+use super::InputSetId;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Hedge {
+    Very,
+    Somewhat,
+    Extremely,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Antecedent {
+    Is(InputSetId),
+    And(Vec<Antecedent>),
+    Or(Vec<Antecedent>),
+    Not(Box<Antecedent>),
+    Hedge(Hedge, Box<Antecedent>),
+}
+
+impl Hedge {
+    pub fn apply(self, membership: f32) -> f32 {
+        match self {
+            Hedge::Very => membership * membership,
+            Hedge::Somewhat => f32::sqrt(membership),
+            Hedge::Extremely => membership * membership * membership,
+        }
+    }
+}
+
+impl Antecedent {
+    pub fn is(input_set: InputSetId) -> Self {
+        Antecedent::Is(input_set)
+    }
+
+    pub fn all(input_sets: &[InputSetId]) -> Self {
+        Antecedent::And(input_sets.iter().map(|&id| Antecedent::Is(id)).collect())
+    }
+
+    pub fn any(input_sets: &[InputSetId]) -> Self {
+        Antecedent::Or(input_sets.iter().map(|&id| Antecedent::Is(id)).collect())
+    }
+
+    pub fn and(self, other: Antecedent) -> Self {
+        match self {
+            Antecedent::And(mut terms) => {
+                terms.push(other);
+                Antecedent::And(terms)
+            },
+            term => Antecedent::And(vec![term, other]),
+        }
+    }
+
+    pub fn or(self, other: Antecedent) -> Self {
+        match self {
+            Antecedent::Or(mut terms) => {
+                terms.push(other);
+                Antecedent::Or(terms)
+            },
+            term => Antecedent::Or(vec![term, other]),
+        }
+    }
+
+    pub fn very(self) -> Self {
+        Antecedent::Hedge(Hedge::Very, Box::new(self))
+    }
+
+    pub fn somewhat(self) -> Self {
+        Antecedent::Hedge(Hedge::Somewhat, Box::new(self))
+    }
+
+    pub fn extremely(self) -> Self {
+        Antecedent::Hedge(Hedge::Extremely, Box::new(self))
+    }
+
+    // Splits the antecedent at its ORs into alternatives that hold no OR
+    // outside a NOT or a hedge. The antecedent is the OR of them.
+    pub fn expand(&self) -> Vec<Antecedent> {
+        match self {
+            Antecedent::Or(terms) => terms.iter().flat_map(|term| term.expand()).collect(),
+            Antecedent::And(terms) => {
+                let mut alternatives = vec![Vec::new()];
+                for term in terms.iter() {
+                    let expanded = term.expand();
+                    alternatives = alternatives.iter()
+                        .flat_map(|alternative| expanded.iter().map(move |term| {
+                            let mut alternative: Vec<Antecedent> = alternative.clone();
+                            match term {
+                                Antecedent::And(inner) => alternative.extend(inner.iter().cloned()),
+                                term => alternative.push(term.clone()),
+                            }
+                            alternative
+                        }))
+                        .collect();
+                }
+                alternatives.into_iter().map(Antecedent::And).collect()
+            },
+            term => vec![term.clone()],
+        }
+    }
+
+    pub fn collect_input_sets(&self, input_sets: &mut Vec<InputSetId>) {
+        match self {
+            Antecedent::Is(id) => input_sets.push(*id),
+            Antecedent::And(terms) | Antecedent::Or(terms) => {
+                for term in terms.iter() {
+                    term.collect_input_sets(input_sets);
+                }
+            },
+            Antecedent::Not(term) | Antecedent::Hedge(_, term) =>
+                term.collect_input_sets(input_sets),
+        }
+    }
+}
+
+impl std::ops::Not for Antecedent {
+    type Output = Antecedent;
+
+    fn not(self) -> Antecedent {
+        Antecedent::Not(Box::new(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_hedges() {
+        assert_relative_eq!(Hedge::Very.apply(0.5), 0.25);
+        assert_relative_eq!(Hedge::Somewhat.apply(0.25), 0.5);
+        assert_relative_eq!(Hedge::Extremely.apply(0.5), 0.125);
+    }
+
+    #[test]
+    fn test_builders() {
+        let a = InputSetId { id: 0 };
+        let b = InputSetId { id: 1 };
+        let c = InputSetId { id: 2 };
+
+        let antecedent = Antecedent::is(a).and(Antecedent::is(b)).and(!Antecedent::is(c));
+        assert_eq!(
+            antecedent,
+            Antecedent::And(vec![
+                Antecedent::Is(a),
+                Antecedent::Is(b),
+                Antecedent::Not(Box::new(Antecedent::Is(c))),
+            ]));
+
+        let antecedent = Antecedent::any(&[a, b]).or(Antecedent::is(c).very());
+        let mut input_sets = Vec::new();
+        antecedent.collect_input_sets(&mut input_sets);
+        assert_eq!(input_sets, vec![a, b, c]);
+    }
+
+    #[test]
+    fn test_expand() {
+        let a = InputSetId { id: 0 };
+        let b = InputSetId { id: 1 };
+        let c = InputSetId { id: 2 };
+        let d = InputSetId { id: 3 };
+
+        let antecedent = Antecedent::all(&[a])
+            .and(Antecedent::any(&[b, c]))
+            .and(!Antecedent::any(&[c, d]));
+        let not_c_or_d = !Antecedent::any(&[c, d]);
+        assert_eq!(
+            antecedent.expand(),
+            vec![
+                Antecedent::And(vec![Antecedent::Is(a), Antecedent::Is(b), not_c_or_d.clone()]),
+                Antecedent::And(vec![Antecedent::Is(a), Antecedent::Is(c), not_c_or_d]),
+            ]);
+
+        let antecedent = Antecedent::any(&[a, b]).or(Antecedent::all(&[c, d]));
+        assert_eq!(
+            antecedent.expand(),
+            vec![Antecedent::Is(a), Antecedent::Is(b), Antecedent::all(&[c, d])]);
+    }
+}
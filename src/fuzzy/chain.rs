@@ -0,0 +1,175 @@
+use super::*;
+
+impl Fuzzy {
+    pub fn produces(&self, rule_set: RuleSetId, output: OutputId) -> bool {
+        self.rule_sets[rule_set.id].rules.iter()
+            .any(|rule| self.output_sets[self.rules[rule.id].output_set.id].output == output)
+    }
+
+    fn reads(&self, rule_set: RuleSetId, input: InputId) -> bool {
+        let rules = &self.rule_sets[rule_set.id].rules;
+        self.rule_inputs(rules).contains(&input) || rules.iter().any(|rule| {
+            match self.output_sets[self.rules[rule.id].output_set.id].term.consequent() {
+                Some(Consequent::Linear { coefficients, .. }) =>
+                    coefficients.iter().any(|&(id, _)| id == input),
+                _ => false,
+            }
+        })
+    }
+
+    // Orders the rule sets so that each one runs after every rule set
+    // whose outputs are linked to its inputs, otherwise keeping the given
+    // order.
+    pub(super) fn order_chain(
+        &self, rule_sets: &[RuleSetId], links: &[Link])
+        -> Result<Vec<RuleSetId>, FuzzyError>
+    {
+        for &rule_set in rule_sets.iter() {
+            if rule_set.id >= self.rule_sets.len() {
+                return Err(FuzzyError::UnknownRuleSet(rule_set));
+            }
+        }
+        for link in links.iter() {
+            if link.output.id >= self.outputs.len() {
+                return Err(FuzzyError::UnknownOutput(link.output));
+            }
+            if link.input.id >= self.inputs.len() {
+                return Err(FuzzyError::UnknownInput(link.input));
+            }
+        }
+
+        let feeds = |from: RuleSetId, to: RuleSetId| links.iter()
+            .any(|link| self.produces(from, link.output) && self.reads(to, link.input));
+
+        let mut remaining = rule_sets.to_vec();
+        let mut ordered = Vec::with_capacity(rule_sets.len());
+        while !remaining.is_empty() {
+            let ready = remaining.iter()
+                .position(|&to| !remaining.iter().any(|&from| feeds(from, to)));
+            match ready {
+                Some(i) => ordered.push(remaining.remove(i)),
+                None => return Err(FuzzyError::Cycle {
+                    rule_sets: remaining.iter()
+                        .map(|rule_set| self.rule_sets[rule_set.id].name.clone())
+                        .collect(),
+                }),
+            }
+        }
+        Ok(ordered)
+    }
+
+    pub fn add_chain(
+        &mut self, rule_sets: &[RuleSetId], links: &[Link])
+        -> Result<ChainId, FuzzyError>
+    {
+        let rule_sets = self.order_chain(rule_sets, links)?;
+        let id = self.chains.len();
+        self.chains.push(Chain {
+            name: format!("chain{}", id),
+            rule_sets,
+            links: links.to_vec(),
+        });
+        Ok(ChainId { id })
+    }
+
+    pub fn evaluate_chain(&mut self, chain: ChainId) -> Result<Evaluation, FuzzyError> {
+        let inputs = self.input_values();
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let result = self.evaluate_chain_with(chain, &inputs, &mut scratch);
+        self.scratch = scratch;
+        self.evaluation = result?;
+        Ok(self.evaluation.clone())
+    }
+
+    // Runs the rule sets in order, passing linked outputs on as inputs to
+    // the later ones. Each output is taken from the last stage producing it.
+    pub fn evaluate_chain_with(
+        &self, chain: ChainId, inputs: &[f32], scratch: &mut Scratch)
+        -> Result<Evaluation, FuzzyError>
+    {
+        let chain = self.chains.get(chain.id).ok_or(FuzzyError::UnknownChain(chain))?;
+        let mut inputs = inputs.to_vec();
+        let mut evaluation = Evaluation::default();
+
+        for &rule_set in chain.rule_sets.iter() {
+            let stage = self.evaluate_with(rule_set, &inputs, scratch)?;
+            for id in 0..self.outputs.len() {
+                let output = OutputId { id };
+                if self.produces(rule_set, output) {
+                    evaluation.merge(&stage, output);
+                }
+            }
+            for link in chain.links.iter().filter(|link| self.produces(rule_set, link.output)) {
+                if let Some(input) = inputs.get_mut(link.input.id) {
+                    *input = stage.value(link.output).unwrap_or(f32::NAN);
+                }
+            }
+        }
+
+        Ok(evaluation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_chain() {
+        let mut fuzzy = Fuzzy::new();
+        let distance = fuzzy.add_input(0.0, 10.0);
+        let danger_in = fuzzy.add_input(0.0, 1.0);
+        let danger = fuzzy.add_output(0.0, 1.0);
+        let brake = fuzzy.add_output(0.0, 1.0);
+
+        let near = fuzzy.add_input_set(distance, Shape::LeftShoulder { a: 2.0, b: 8.0 });
+        let far = fuzzy.add_input_set(distance, Shape::RightShoulder { a: 2.0, b: 8.0 });
+        let low = fuzzy.add_output_set(danger, Shape::LeftShoulder { a: 0.0, b: 1.0 });
+        let high = fuzzy.add_output_set(danger, Shape::RightShoulder { a: 0.0, b: 1.0 });
+        let r1 = fuzzy.add_rule(&[near], high);
+        let r2 = fuzzy.add_rule(&[far], low);
+        let assess = fuzzy.add_rule_set(&[r1, r2]);
+
+        let calm = fuzzy.add_input_set(danger_in, Shape::LeftShoulder { a: 0.2, b: 0.8 });
+        let alarmed = fuzzy.add_input_set(danger_in, Shape::RightShoulder { a: 0.2, b: 0.8 });
+        let soft = fuzzy.add_output_set(brake, Shape::LeftShoulder { a: 0.0, b: 1.0 });
+        let hard = fuzzy.add_output_set(brake, Shape::RightShoulder { a: 0.0, b: 1.0 });
+        let r3 = fuzzy.add_rule(&[calm], soft);
+        let r4 = fuzzy.add_rule(&[alarmed], hard);
+        let act = fuzzy.add_rule_set(&[r3, r4]);
+        fuzzy.set_rule_set_name(assess, "assess");
+        fuzzy.set_rule_set_name(act, "act");
+
+        let link = Link { output: danger, input: danger_in };
+        let chain = fuzzy.add_chain(&[act, assess], &[link]).unwrap();
+        assert_eq!(fuzzy.chains[chain.id].rule_sets, &[assess, act]);
+
+        let mut scratch = Scratch::default();
+        let evaluation = fuzzy.evaluate_chain_with(chain, &[3.0, 0.0], &mut scratch).unwrap();
+        let first = fuzzy.evaluate_with(assess, &[3.0, 0.0], &mut scratch).unwrap();
+        let inputs = [3.0, first.value(danger).unwrap()];
+        let second = fuzzy.evaluate_with(act, &inputs, &mut scratch).unwrap();
+        assert_relative_eq!(evaluation.value(danger).unwrap(), first.value(danger).unwrap());
+        assert_relative_eq!(evaluation.value(brake).unwrap(), second.value(brake).unwrap());
+        assert!(evaluation.value(brake).unwrap() > 0.5);
+
+        fuzzy.set_chain_name(chain, "brake");
+        assert_eq!(fuzzy.find_chain("brake"), Some(chain));
+        fuzzy.set_input(distance, 3.0).unwrap();
+        fuzzy.evaluate_chain(chain).unwrap();
+        assert_relative_eq!(fuzzy.get_output(danger).unwrap(), evaluation.value(danger).unwrap());
+        assert_relative_eq!(fuzzy.get_output(brake).unwrap(), evaluation.value(brake).unwrap());
+
+        let text = fuzzy.to_json();
+        let loaded = Fuzzy::from_json(&text).unwrap();
+        assert_eq!(loaded.to_json(), text);
+        assert_eq!(loaded.chains[chain.id].rule_sets, &[assess, act]);
+
+        let feedback = Link { output: brake, input: distance };
+        assert_eq!(
+            fuzzy.add_chain(&[assess, act], &[link, feedback]),
+            Err(FuzzyError::Cycle { rule_sets: vec!["assess".to_string(), "act".to_string()] }));
+    }
+}
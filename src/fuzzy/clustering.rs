@@ -0,0 +1,354 @@
+use nalgebra as na;
+use rand::{Rng, SeedableRng};
+use rand::rngs::SmallRng;
+
+use super::*;
+
+const ZERO_DISTANCE: f64 = 1e-12;
+const REGULARIZATION: f64 = 1e-6;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClusterMethod {
+    CMeans,
+    GustafsonKessel,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Clustering {
+    pub method: ClusterMethod,
+    pub clusters: usize,
+    pub fuzziness: f32,
+    pub iterations: usize,
+    pub tolerance: f32,
+    pub seed: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Clusters {
+    pub centers: Vec<Vec<f32>>,
+    pub spreads: Vec<Vec<f32>>,
+    pub memberships: Vec<Vec<f32>>,
+    pub iterations: usize,
+}
+
+impl Default for Clustering {
+    fn default() -> Self {
+        Clustering {
+            method: ClusterMethod::CMeans,
+            clusters: 3,
+            fuzziness: 2.0,
+            iterations: 100,
+            tolerance: 1e-4,
+            seed: 0,
+        }
+    }
+}
+
+fn weights(memberships: &[Vec<f64>], fuzziness: f64) -> Vec<Vec<f64>> {
+    memberships.iter()
+        .map(|row| row.iter().map(|u| u.powf(fuzziness)).collect())
+        .collect()
+}
+
+fn centers(points: &[na::DVector<f64>], weights: &[Vec<f64>]) -> Vec<na::DVector<f64>> {
+    weights.iter()
+        .map(|row| {
+            let total: f64 = row.iter().sum();
+            let sum = points.iter().zip(row.iter())
+                .fold(na::DVector::zeros(points[0].len()), |sum, (x, w)| sum + x * *w);
+            sum / total
+        })
+        .collect()
+}
+
+fn covariance(
+    points: &[na::DVector<f64>], center: &na::DVector<f64>, weights: &[f64])
+    -> na::DMatrix<f64>
+{
+    let n = center.len();
+    let total: f64 = weights.iter().sum();
+    let sum = points.iter().zip(weights.iter())
+        .fold(na::DMatrix::zeros(n, n), |sum, (x, w)| {
+            let d = x - center;
+            sum + (&d * d.transpose()) * *w
+        });
+    sum / total + na::DMatrix::identity(n, n) * REGULARIZATION
+}
+
+// Gustafson-Kessel replaces the Euclidean norm of every cluster with one
+// induced by its fuzzy covariance, scaled to unit volume, so clusters can
+// stretch along any direction.
+fn norm_matrix(covariance: na::DMatrix<f64>) -> na::DMatrix<f64> {
+    let n = covariance.nrows();
+    let det = covariance.determinant();
+    match covariance.try_inverse() {
+        Some(inverse) if det > 0.0 => inverse * det.powf(1.0 / n as f64),
+        _ => na::DMatrix::identity(n, n),
+    }
+}
+
+fn update_memberships(distances: &[Vec<f64>], fuzziness: f64) -> Vec<Vec<f64>> {
+    let clusters = distances.len();
+    let samples = distances[0].len();
+    let mut memberships = vec![vec![0.0; samples]; clusters];
+    for k in 0..samples {
+        let zeros = (0..clusters).filter(|&i| distances[i][k] < ZERO_DISTANCE).count();
+        for i in 0..clusters {
+            memberships[i][k] = if zeros > 0 {
+                if distances[i][k] < ZERO_DISTANCE { 1.0 / zeros as f64 } else { 0.0 }
+            }
+            else {
+                let sum: f64 = (0..clusters)
+                    .map(|j| (distances[i][k] / distances[j][k]).powf(1.0 / (fuzziness - 1.0)))
+                    .sum();
+                1.0 / sum
+            };
+        }
+    }
+    memberships
+}
+
+pub fn cluster(points: &[Vec<f32>], clustering: &Clustering) -> Clusters {
+    assert!(clustering.clusters > 0 && clustering.clusters <= points.len());
+    assert!(clustering.fuzziness > 1.0);
+
+    let fuzziness = clustering.fuzziness as f64;
+    let points: Vec<na::DVector<f64>> = points.iter()
+        .map(|point| na::DVector::from_iterator(point.len(), point.iter().map(|&x| x as f64)))
+        .collect();
+
+    let mut rng = SmallRng::seed_from_u64(clustering.seed);
+    let rows: Vec<Vec<f64>> = points.iter()
+        .map(|_| {
+            let row: Vec<f64> = (0..clustering.clusters).map(|_| rng.gen_range(0.01, 1.0)).collect();
+            let total: f64 = row.iter().sum();
+            row.iter().map(|u| u / total).collect()
+        })
+        .collect();
+    let mut memberships: Vec<Vec<f64>> = (0..clustering.clusters)
+        .map(|i| rows.iter().map(|row| row[i]).collect())
+        .collect();
+
+    let mut iterations = 0;
+    while iterations < clustering.iterations {
+        iterations += 1;
+
+        let w = weights(&memberships, fuzziness);
+        let v = centers(&points, &w);
+        let distances: Vec<Vec<f64>> = v.iter().zip(w.iter())
+            .map(|(center, row)| {
+                let norm = match clustering.method {
+                    ClusterMethod::CMeans => None,
+                    ClusterMethod::GustafsonKessel =>
+                        Some(norm_matrix(covariance(&points, center, row))),
+                };
+                points.iter()
+                    .map(|x| {
+                        let d = x - center;
+                        match norm {
+                            None => d.norm_squared(),
+                            Some(ref a) => (d.transpose() * a * &d)[0].max(0.0),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let next = update_memberships(&distances, fuzziness);
+        let change = next.iter().flatten().zip(memberships.iter().flatten())
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        memberships = next;
+        if change < clustering.tolerance as f64 {
+            break;
+        }
+    }
+
+    let w = weights(&memberships, fuzziness);
+    let v = centers(&points, &w);
+    let spreads = v.iter().zip(w.iter())
+        .map(|(center, row)| {
+            let f = covariance(&points, center, row);
+            (0..center.len()).map(|d| (f[(d, d)].sqrt() as f32).max(f32::EPSILON)).collect()
+        })
+        .collect();
+
+    Clusters {
+        centers: v.iter().map(|center| center.iter().map(|&x| x as f32).collect()).collect(),
+        spreads,
+        memberships: memberships.iter()
+            .map(|row| row.iter().map(|&u| u as f32).collect())
+            .collect(),
+        iterations,
+    }
+}
+
+impl Clusters {
+    // Projects every cluster onto one axis as a Gaussian with the fuzzy
+    // standard deviation along that axis, ordered by center.
+    pub fn shapes(&self, axis: usize) -> Vec<Shape> {
+        let mut shapes: Vec<Shape> = self.centers.iter().zip(self.spreads.iter())
+            .map(|(center, spread)| Shape::Gaussian { mean: center[axis], sigma: spread[axis] })
+            .collect();
+        shapes.sort_by(|a, b| a.peak().partial_cmp(&b.peak()).unwrap());
+        shapes
+    }
+}
+
+impl Fuzzy {
+    // Clusters the samples in the space of the given inputs, each scaled
+    // to [0, 1] by its sampling range, and adds the projected clusters as input
+    // sets named cluster0, cluster1, ... from left to right.
+    pub fn add_clustered_sets(
+        &mut self, inputs: &[InputId], samples: &[Sample], clustering: &Clustering)
+        -> Result<Vec<Vec<InputSetId>>, FuzzyError>
+    {
+        for &input in inputs.iter() {
+            if input.id >= self.inputs.len() {
+                return Err(FuzzyError::UnknownInput(input));
+            }
+        }
+        if clustering.clusters == 0 || clustering.clusters > samples.len() {
+            return Err(FuzzyError::ClusterCount {
+                clusters: clustering.clusters,
+                samples: samples.len(),
+            });
+        }
+        if clustering.fuzziness.is_nan() || clustering.fuzziness <= 1.0 {
+            return Err(FuzzyError::Fuzziness(clustering.fuzziness));
+        }
+        for sample in samples.iter() {
+            if sample.inputs.len() != self.inputs.len() {
+                return Err(FuzzyError::InputCount {
+                    expected: self.inputs.len(),
+                    found: sample.inputs.len(),
+                });
+            }
+            for &input in inputs.iter() {
+                if sample.inputs[input.id].is_nan() {
+                    return Err(FuzzyError::NotANumber { input: self.inputs[input.id].name.clone() });
+                }
+            }
+        }
+
+        let ranges: Vec<(f32, f32)> = inputs.iter()
+            .map(|&input| {
+                let (min, max) = self.sample_range(input);
+                (min, f32::max(max - min, f32::EPSILON))
+            })
+            .collect();
+        let points: Vec<Vec<f32>> = samples.iter()
+            .map(|sample| inputs.iter().zip(ranges.iter())
+                .map(|(input, &(min, width))| (sample.inputs[input.id] - min) / width)
+                .collect())
+            .collect();
+        let clusters = cluster(&points, clustering);
+
+        let mut sets = Vec::with_capacity(inputs.len());
+        for (axis, (&input, &(min, width))) in inputs.iter().zip(ranges.iter()).enumerate() {
+            let ids: Vec<InputSetId> = clusters.shapes(axis).into_iter().enumerate()
+                .map(|(i, shape)| {
+                    let shape = match shape {
+                        Shape::Gaussian { mean, sigma } =>
+                            Shape::Gaussian { mean: min + mean * width, sigma: sigma * width },
+                        shape => shape,
+                    };
+                    let id = self.add_input_set(input, shape);
+                    self.set_input_set_name(id, &format!("cluster{}", i));
+                    id
+                })
+                .collect();
+            sets.push(ids);
+        }
+        Ok(sets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn blobs() -> Vec<Vec<f32>> {
+        let mut points = Vec::new();
+        for i in 0..20 {
+            let t = i as f32 / 19.0 - 0.5;
+            points.push(vec![0.2 + 0.1 * t, 0.3 + 0.02 * t]);
+            points.push(vec![0.8 + 0.02 * t, 0.7 + 0.1 * t]);
+        }
+        points
+    }
+
+    #[test]
+    fn test_cluster() {
+        for &method in [ClusterMethod::CMeans, ClusterMethod::GustafsonKessel].iter() {
+            let clustering = Clustering { method, clusters: 2, ..Clustering::default() };
+            let clusters = cluster(&blobs(), &clustering);
+            assert_eq!(clusters.centers.len(), 2);
+            assert!(clusters.iterations < clustering.iterations);
+
+            let shapes = clusters.shapes(0);
+            assert_relative_eq!(shapes[0].peak().unwrap(), 0.2, epsilon = 0.01);
+            assert_relative_eq!(shapes[1].peak().unwrap(), 0.8, epsilon = 0.01);
+            let shapes = clusters.shapes(1);
+            assert_relative_eq!(shapes[0].peak().unwrap(), 0.3, epsilon = 0.01);
+            assert_relative_eq!(shapes[1].peak().unwrap(), 0.7, epsilon = 0.01);
+
+            for k in 0..blobs().len() {
+                let total: f32 = clusters.memberships.iter().map(|row| row[k]).sum();
+                assert_relative_eq!(total, 1.0, epsilon = 1e-4);
+            }
+        }
+
+        // The first blob is stretched along x, which the projected spread follows.
+        let clusters = cluster(&blobs(), &Clustering { clusters: 2, ..Clustering::default() });
+        let first = if clusters.centers[0][0] < 0.5 { 0 } else { 1 };
+        assert!(clusters.spreads[first][0] > clusters.spreads[first][1]);
+        assert_eq!(clusters, cluster(&blobs(), &Clustering { clusters: 2, ..Clustering::default() }));
+    }
+
+    #[test]
+    fn test_add_clustered_sets() {
+        let mut fuzzy = Fuzzy::new();
+        let x = fuzzy.add_input(0.0, 10.0);
+        let y = fuzzy.add_input(-1.0, 1.0);
+        fuzzy.set_input_name(x, "x");
+        let samples: Vec<Sample> = blobs().iter()
+            .map(|point| Sample {
+                inputs: vec![point[0] * 10.0, point[1] * 2.0 - 1.0],
+                outputs: Vec::new(),
+            })
+            .collect();
+
+        let clustering = Clustering { clusters: 2, ..Clustering::default() };
+        let sets = fuzzy.add_clustered_sets(&[x, y], &samples, &clustering).unwrap();
+        assert_eq!(sets.len(), 2);
+        assert_eq!(sets[0].len(), 2);
+        assert_eq!(fuzzy.input_set_name(sets[0][1]), "x.cluster1");
+        assert_relative_eq!(fuzzy.input_set_function(sets[0][0]).eval(2.0), 1.0, epsilon = 0.01);
+        assert_relative_eq!(fuzzy.input_set_function(sets[1][1]).eval(0.4), 1.0, epsilon = 0.01);
+        assert!(fuzzy.input_set_function(sets[0][0]).eval(8.0) < 0.01);
+
+        let mut open = Fuzzy::new();
+        let z = open.add_input(f32::NEG_INFINITY, f32::INFINITY);
+        open.add_input_set(z, Shape::LeftShoulder { a: 0.0, b: 10.0 });
+        let z_samples: Vec<Sample> = samples.iter()
+            .map(|sample| Sample { inputs: vec![sample.inputs[0]], outputs: Vec::new() })
+            .collect();
+        let open_sets = open.add_clustered_sets(&[z], &z_samples, &clustering).unwrap();
+        assert_relative_eq!(open.input_set_function(open_sets[0][0]).eval(2.0), 1.0, epsilon = 0.01);
+
+        assert_eq!(
+            fuzzy.add_clustered_sets(&[x], &samples[..1], &clustering),
+            Err(FuzzyError::ClusterCount { clusters: 2, samples: 1 }));
+        let crisp = Clustering { fuzziness: 1.0, ..clustering };
+        assert_eq!(
+            fuzzy.add_clustered_sets(&[x], &samples, &crisp),
+            Err(FuzzyError::Fuzziness(1.0)));
+        let mut gap = samples.clone();
+        gap[3].inputs[0] = f32::NAN;
+        assert_eq!(
+            fuzzy.add_clustered_sets(&[x], &gap, &clustering),
+            Err(FuzzyError::NotANumber { input: "x".to_string() }));
+    }
+}
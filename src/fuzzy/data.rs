@@ -0,0 +1,99 @@
+use super::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub inputs: Vec<f32>,
+    pub outputs: Vec<f32>,
+}
+
+enum Column {
+    Input(InputId),
+    Output(OutputId),
+}
+
+impl Sample {
+    pub fn output(&self, output: OutputId) -> Option<f32> {
+        self.outputs.get(output.id).cloned().filter(|value| !value.is_nan())
+    }
+}
+
+impl Fuzzy {
+    // Columns are matched to inputs and outputs by name, inputs missing
+    // from the file keep their current value and missing outputs are NaN.
+    pub fn read_samples(&self, csv: &str) -> Result<Vec<Sample>, ParseError> {
+        let error = |line: usize, column: usize, message: String| {
+            ParseError { line: line + 1, column: column + 1, message }
+        };
+
+        let mut lines = csv.lines().enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let columns = match lines.next() {
+            Some((_, header)) => header.split(',')
+                .enumerate()
+                .map(|(column, name)| {
+                    let name = name.trim();
+                    self.find_input(name).map(Column::Input)
+                        .or_else(|| self.find_output(name).map(Column::Output))
+                        .ok_or_else(|| error(0, column, format!("unknown column `{}`", name)))
+                })
+                .collect::<Result<Vec<Column>, ParseError>>()?,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut samples = Vec::new();
+        for (line, text) in lines {
+            let mut sample = Sample {
+                inputs: self.input_values(),
+                outputs: vec![f32::NAN; self.outputs.len()],
+            };
+            let fields: Vec<&str> = text.split(',').collect();
+            if fields.len() != columns.len() {
+                return Err(error(line, 0, format!(
+                    "expected {} fields, found {}", columns.len(), fields.len())));
+            }
+            for (column, (field, kind)) in fields.iter().zip(columns.iter()).enumerate() {
+                let value: f32 = field.trim().parse()
+                    .map_err(|_| error(line, column, format!("invalid number `{}`", field.trim())))?;
+                match *kind {
+                    Column::Input(id) => sample.inputs[id.id] = value,
+                    Column::Output(id) => sample.outputs[id.id] = value,
+                }
+            }
+            samples.push(sample);
+        }
+        Ok(samples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_samples() {
+        let mut fuzzy = Fuzzy::new();
+        let i1 = fuzzy.add_input(0.0, 1.0);
+        let i2 = fuzzy.add_input(0.0, 1.0);
+        let o1 = fuzzy.add_output(0.0, 1.0);
+        let o2 = fuzzy.add_output(0.0, 1.0);
+        fuzzy.set_input_name(i1, "a");
+        fuzzy.set_input_name(i2, "b");
+        fuzzy.set_output_name(o1, "x");
+        fuzzy.set_output_name(o2, "y");
+        fuzzy.set_input(i2, 0.5).unwrap();
+
+        let samples = fuzzy.read_samples("x, a\n0.25, 1\n\n0.75,0\n").unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].inputs, vec![1.0, 0.5]);
+        assert_eq!(samples[1].output(o1), Some(0.75));
+        assert_eq!(samples[1].output(o2), None);
+
+        let err = fuzzy.read_samples("a,z\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 2));
+        assert_eq!(err.message, "unknown column `z`");
+        let err = fuzzy.read_samples("a,x\n1,2\n1,foo\n").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 2));
+        let err = fuzzy.read_samples("a,x\n1\n").unwrap_err();
+        assert_eq!(err.message, "expected 2 fields, found 1");
+    }
+}
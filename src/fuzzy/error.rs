@@ -0,0 +1,77 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use super::{InputId, OutputId, RuleSetId, ChainId};
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RangePolicy {
+    Accept,
+    Clamp,
+    Reject,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fallback {
+    Default(f32),
+    HoldPrevious,
+    Undefined,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FuzzyError {
+    UnknownInput(InputId),
+    UnknownOutput(OutputId),
+    UnknownRuleSet(RuleSetId),
+    Undefined { output: String },
+    InputCount { expected: usize, found: usize },
+    NotANumber { input: String },
+    OutOfRange { input: String, value: f32, min: f32, max: f32 },
+    NotSugeno { output: String },
+    NotMamdani { output: String },
+    NoSamples,
+    ClusterCount { clusters: usize, samples: usize },
+    Fuzziness(f32),
+    Layout { expected: String, found: String },
+    UnknownChain(ChainId),
+    Cycle { rule_sets: Vec<String> },
+    TooFewSamples { samples: usize },
+    TooManyInputs { rule_set: String, inputs: usize },
+}
+
+impl fmt::Display for FuzzyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use FuzzyError::*;
+
+        match self {
+            UnknownInput(id) => write!(f, "unknown input {:?}", id),
+            UnknownOutput(id) => write!(f, "unknown output {:?}", id),
+            UnknownRuleSet(id) => write!(f, "unknown rule set {:?}", id),
+            Undefined { output } => write!(f, "output `{}` is undefined, no rule fired", output),
+            InputCount { expected, found } =>
+                write!(f, "expected {} input values, found {}", expected, found),
+            NotANumber { input } => write!(f, "input `{}` is not a number", input),
+            OutOfRange { input, value, min, max } =>
+                write!(f, "input `{}` = {} is out of range [{}, {}]", input, value, min, max),
+            NotSugeno { output } => write!(f, "output `{}` is not a Sugeno output", output),
+            NotMamdani { output } => write!(f, "output `{}` is not a Mamdani output", output),
+            NoSamples => write!(f, "no sample fires any rule"),
+            ClusterCount { clusters, samples } =>
+                write!(f, "cannot form {} clusters from {} samples", clusters, samples),
+            Fuzziness(fuzziness) =>
+                write!(f, "fuzziness {} should be greater than 1", fuzziness),
+            Layout { expected, found } => write!(f, "expected {}, found {}", expected, found),
+            UnknownChain(id) => write!(f, "unknown chain {:?}", id),
+            Cycle { rule_sets } =>
+                write!(f, "rule sets `{}` feed each other in a cycle", rule_sets.join("`, `")),
+            TooFewSamples { samples } =>
+                write!(f, "expected at least 2 samples per input, found {}", samples),
+            TooManyInputs { rule_set, inputs } =>
+                write!(f, "rule set `{}` reads {} inputs, too many to sample", rule_set, inputs),
+        }
+    }
+}
+
+impl std::error::Error for FuzzyError {}
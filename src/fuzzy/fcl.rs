@@ -0,0 +1,666 @@
+use std::fmt::Write;
+
+use super::*;
+use super::parser::{Parser, TokenKind, error};
+
+fn and_name(norm: TNorm) -> &'static str {
+    match norm {
+        TNorm::Minimum => "MIN",
+        TNorm::Product => "PROD",
+        TNorm::Lukasiewicz => "BDIF",
+        TNorm::Einstein => "EPROD",
+        TNorm::Drastic => "DPROD",
+    }
+}
+
+fn or_name(norm: SNorm) -> &'static str {
+    match norm {
+        SNorm::Maximum => "MAX",
+        SNorm::ProbabilisticSum => "ASUM",
+        SNorm::BoundedSum => "BSUM",
+        SNorm::Einstein => "ESUM",
+        SNorm::Drastic => "DSUM",
+    }
+}
+
+fn act_name(implication: Implication) -> &'static str {
+    match implication {
+        Implication::Minimum => "MIN",
+        Implication::Product => "PROD",
+    }
+}
+
+fn method_name(defuzzifier: Defuzzifier) -> &'static str {
+    match defuzzifier {
+        Defuzzifier::Centroid => "COG",
+        Defuzzifier::Bisector => "COA",
+        Defuzzifier::MeanOfMaximum => "MM",
+        Defuzzifier::SmallestOfMaximum => "LM",
+        Defuzzifier::LargestOfMaximum => "RM",
+        Defuzzifier::WeightedAverage => "COGS",
+    }
+}
+
+fn parse_and(name: &str) -> Option<TNorm> {
+    [TNorm::Minimum, TNorm::Product, TNorm::Lukasiewicz, TNorm::Einstein, TNorm::Drastic]
+        .iter().cloned().find(|&norm| and_name(norm) == name)
+}
+
+fn parse_or(name: &str) -> Option<SNorm> {
+    [SNorm::Maximum, SNorm::ProbabilisticSum, SNorm::BoundedSum, SNorm::Einstein, SNorm::Drastic]
+        .iter().cloned().find(|&norm| or_name(norm) == name)
+}
+
+fn parse_act(name: &str) -> Option<Implication> {
+    [Implication::Minimum, Implication::Product]
+        .iter().cloned().find(|&implication| act_name(implication) == name)
+}
+
+fn parse_method(name: &str) -> Option<Defuzzifier> {
+    use Defuzzifier::*;
+
+    [Centroid, Bisector, MeanOfMaximum, SmallestOfMaximum, LargestOfMaximum, WeightedAverage]
+        .iter().cloned().find(|&defuzzifier| method_name(defuzzifier) == name)
+}
+
+fn write_points(text: &mut String, points: &[(f32, f32)]) {
+    let points: Vec<String> = points.iter()
+        .map(|&(x, y)| format!("({}, {})", x, y))
+        .collect();
+    text.push_str(&points.join(" "));
+}
+
+fn write_shape(text: &mut String, shape: &Shape) {
+    use Shape::*;
+
+    match *shape {
+        Triangle { a, b, c } => write!(text, "trian {} {} {}", a, b, c).unwrap(),
+        Trapezoid { a, b, c, d } => write!(text, "trape {} {} {} {}", a, b, c, d).unwrap(),
+        LeftShoulder { a, b } => write_points(text, &[(a, 1.0), (b, 0.0)]),
+        RightShoulder { a, b } => write_points(text, &[(a, 0.0), (b, 1.0)]),
+        Gaussian { mean, sigma } => write!(text, "gauss {} {}", mean, sigma).unwrap(),
+        Bell { a, b, c } => write!(text, "gbell {} {} {}", a, b, c).unwrap(),
+        Sigmoid { slope, center } => write!(text, "sigm {} {}", slope, center).unwrap(),
+        PiecewiseLinear(ref points) => write_points(text, points),
+    }
+}
+
+pub(super) fn sampled_points(f: &MembershipFunction, min: f32, max: f32) -> Vec<(f32, f32)> {
+    (0..=DEFAULT_RESOLUTION)
+        .map(|i| min + (max - min) * i as f32 / DEFAULT_RESOLUTION as f32)
+        .map(|x| (x, f.eval(x)))
+        .collect()
+}
+
+// FCL only has type-1 shapes, so an interval set keeps its upper membership
+// function and a custom one is sampled. The term is followed by a comment
+// saying what was lost.
+fn write_membership(text: &mut String, f: &MembershipFunction, min: f32, max: f32) {
+    match f {
+        MembershipFunction::Shape(shape) => {
+            write_shape(text, shape);
+            text.push(';');
+        },
+        MembershipFunction::Interval { upper, .. } => {
+            write_shape(text, upper);
+            text.push_str("; (* interval set, lower membership function dropped *)");
+        },
+        MembershipFunction::Custom(_) => {
+            write_points(text, &sampled_points(f, min, max));
+            text.push_str("; (* sampled from a custom function *)");
+        },
+    }
+}
+
+impl Fuzzy {
+    fn write_consequent(&self, text: &mut String, consequent: &Consequent) {
+        match consequent {
+            Consequent::Constant(value) => write!(text, "{}", value).unwrap(),
+            Consequent::Linear { coefficients, constant } => {
+                write!(text, "FUNCTION {}", constant).unwrap();
+                for &(input, coefficient) in coefficients.iter() {
+                    write!(text, " + {} * {}", coefficient, self.inputs[input.id].name).unwrap();
+                }
+            },
+        }
+    }
+
+    // Interval type-2 sets are written as their upper membership function,
+    // so they load back as type-1 sets.
+    pub fn to_fcl(&self, name: &str) -> String {
+        let mut text = String::new();
+        writeln!(text, "FUNCTION_BLOCK {}", name).unwrap();
+
+        text.push_str("\nVAR_INPUT\n");
+        for input in self.inputs.iter() {
+            writeln!(text, "    {} : REAL;", input.name).unwrap();
+        }
+        text.push_str("END_VAR\n\nVAR_OUTPUT\n");
+        for output in self.outputs.iter() {
+            writeln!(text, "    {} : REAL;", output.name).unwrap();
+        }
+        text.push_str("END_VAR\n");
+
+        for (id, input) in self.inputs.iter().enumerate() {
+            writeln!(text, "\nFUZZIFY {}", input.name).unwrap();
+            if input.min.is_finite() && input.max.is_finite() {
+                writeln!(text, "    RANGE := ({} .. {});", input.min, input.max).unwrap();
+            }
+            for set in self.input_sets.iter().filter(|set| set.input.id == id) {
+                write!(text, "    TERM {} := ", set.name).unwrap();
+                write_membership(&mut text, &set.f, input.min, input.max);
+                text.push('\n');
+            }
+            text.push_str("END_FUZZIFY\n");
+        }
+
+        for (id, output) in self.outputs.iter().enumerate() {
+            writeln!(text, "\nDEFUZZIFY {}", output.name).unwrap();
+            writeln!(text, "    RANGE := ({} .. {});", output.min, output.max).unwrap();
+            for set in self.output_sets.iter().filter(|set| set.output.id == id) {
+                write!(text, "    TERM {} := ", set.name).unwrap();
+                match set.term {
+                    OutputTerm::Membership(ref f) =>
+                        write_membership(&mut text, f, output.min, output.max),
+                    OutputTerm::Consequent(ref consequent) => {
+                        self.write_consequent(&mut text, consequent);
+                        text.push(';');
+                    },
+                }
+                text.push('\n');
+            }
+            let method =
+                match output.kind {
+                    OutputKind::Mamdani => method_name(output.defuzzifier),
+                    OutputKind::Sugeno => "COGS",
+                };
+            writeln!(text, "    METHOD : {};", method).unwrap();
+            match output.fallback {
+                Fallback::Default(value) => writeln!(text, "    DEFAULT := {};", value).unwrap(),
+                Fallback::HoldPrevious => text.push_str("    DEFAULT := NC;\n"),
+                Fallback::Undefined => (),
+            }
+            text.push_str("END_DEFUZZIFY\n");
+        }
+
+        for rule_set in self.rule_sets.iter() {
+            writeln!(text, "\nRULEBLOCK {}", rule_set.name).unwrap();
+            writeln!(text, "    AND : {};", and_name(rule_set.operators.and)).unwrap();
+            writeln!(text, "    OR : {};", or_name(rule_set.operators.or)).unwrap();
+            writeln!(text, "    ACCU : {};", or_name(rule_set.operators.accumulation)).unwrap();
+            writeln!(text, "    ACT : {};", act_name(rule_set.operators.implication)).unwrap();
+            for (number, &rule) in rule_set.rules.iter().enumerate() {
+                writeln!(text, "    RULE {} : {};", number + 1, self.format_rule(rule)).unwrap();
+            }
+            text.push_str("END_RULEBLOCK\n");
+        }
+
+        text.push_str("\nEND_FUNCTION_BLOCK\n");
+        text
+    }
+
+    pub fn from_fcl(text: &str) -> Result<Fuzzy, ParseError> {
+        let mut loader = Loader {
+            parser: Parser::new(text)?,
+            fuzzy: Fuzzy::new(),
+        };
+        loader.function_block()?;
+        Ok(loader.fuzzy)
+    }
+}
+
+enum Term {
+    Membership(Shape),
+    Consequent(Consequent),
+}
+
+struct Loader {
+    parser: Parser,
+    fuzzy: Fuzzy,
+}
+
+impl Loader {
+    fn keyword(&mut self, what: &str) -> Result<String, ParseError> {
+        let (name, _) = self.parser.expect_ident(what)?;
+        Ok(name.to_uppercase())
+    }
+
+    fn function_block(&mut self) -> Result<(), ParseError> {
+        self.parser.expect_keyword("FUNCTION_BLOCK")?;
+        if let TokenKind::Ident(_) = self.parser.peek().kind {
+            if !self.parser.at_keyword("VAR_INPUT") && !self.parser.at_keyword("VAR_OUTPUT") {
+                self.parser.next();
+            }
+        }
+
+        loop {
+            let token = self.parser.peek().clone();
+            match self.keyword("a function block section")?.as_str() {
+                "VAR_INPUT" => self.variables(true)?,
+                "VAR_OUTPUT" => self.variables(false)?,
+                "FUZZIFY" => self.fuzzify()?,
+                "DEFUZZIFY" => self.defuzzify()?,
+                "RULEBLOCK" => self.rule_block()?,
+                "END_FUNCTION_BLOCK" => break,
+                section => return Err(error(token.line, token.column,
+                                            format!("unknown section `{}`", section))),
+            }
+        }
+
+        if !self.parser.at_end() {
+            return Err(self.parser.unexpected("end of input"));
+        }
+        Ok(())
+    }
+
+    fn variables(&mut self, inputs: bool) -> Result<(), ParseError> {
+        while !self.parser.at_keyword("END_VAR") {
+            let (name, token) = self.parser.expect_ident("a variable name")?;
+            if self.fuzzy.find_input(&name).is_some() || self.fuzzy.find_output(&name).is_some() {
+                return Err(error(token.line, token.column,
+                                 format!("variable `{}` is declared twice", name)));
+            }
+
+            self.parser.expect_symbol(":")?;
+            self.parser.expect_keyword("REAL")?;
+            self.parser.expect_symbol(";")?;
+
+            if inputs {
+                let id = self.fuzzy.add_input(f32::NEG_INFINITY, f32::INFINITY);
+                self.fuzzy.set_input_name(id, &name);
+            }
+            else {
+                let id = self.fuzzy.add_output(0.0, 0.0);
+                self.fuzzy.set_output_name(id, &name);
+            }
+        }
+        self.parser.next();
+        Ok(())
+    }
+
+    fn range(&mut self) -> Result<(f32, f32), ParseError> {
+        self.parser.expect_symbol(":=")?;
+        if self.parser.peek().kind != TokenKind::LeftParen {
+            return Err(self.parser.unexpected("`(`"));
+        }
+        self.parser.next();
+        let min = self.parser.expect_number("a number")?;
+        self.parser.expect_symbol("..")?;
+        let max = self.parser.expect_number("a number")?;
+        if self.parser.peek().kind != TokenKind::RightParen {
+            return Err(self.parser.unexpected("`)`"));
+        }
+        self.parser.next();
+        self.parser.expect_symbol(";")?;
+        Ok((min, max))
+    }
+
+    fn numbers(&mut self, count: usize) -> Result<Vec<f32>, ParseError> {
+        (0..count).map(|_| self.parser.expect_number("a number")).collect()
+    }
+
+    fn term(&mut self) -> Result<(String, Term), ParseError> {
+        let (name, _) = self.parser.expect_ident("a term name")?;
+        self.parser.expect_symbol(":=")?;
+
+        let token = self.parser.peek().clone();
+        let term =
+            match token.kind {
+                TokenKind::LeftParen => {
+                    let mut points = Vec::new();
+                    while self.parser.peek().kind == TokenKind::LeftParen {
+                        self.parser.next();
+                        let x = self.parser.expect_number("a number")?;
+                        self.parser.expect_symbol(",")?;
+                        let y = self.parser.expect_number("a number")?;
+                        if self.parser.peek().kind != TokenKind::RightParen {
+                            return Err(self.parser.unexpected("`)`"));
+                        }
+                        self.parser.next();
+                        points.push((x, y));
+                    }
+                    Term::Membership(Shape::PiecewiseLinear(points))
+                },
+                TokenKind::Number(value) => {
+                    self.parser.next();
+                    Term::Consequent(Consequent::Constant(value))
+                },
+                TokenKind::Ident(_) => {
+                    match self.keyword("a membership function")?.as_str() {
+                        "TRIAN" => {
+                            let p = self.numbers(3)?;
+                            Term::Membership(Shape::Triangle { a: p[0], b: p[1], c: p[2] })
+                        },
+                        "TRAPE" => {
+                            let p = self.numbers(4)?;
+                            Term::Membership(Shape::Trapezoid { a: p[0], b: p[1], c: p[2], d: p[3] })
+                        },
+                        "GAUSS" => {
+                            let p = self.numbers(2)?;
+                            Term::Membership(Shape::Gaussian { mean: p[0], sigma: p[1] })
+                        },
+                        "GBELL" => {
+                            let p = self.numbers(3)?;
+                            Term::Membership(Shape::Bell { a: p[0], b: p[1], c: p[2] })
+                        },
+                        "SIGM" => {
+                            let p = self.numbers(2)?;
+                            Term::Membership(Shape::Sigmoid { slope: p[0], center: p[1] })
+                        },
+                        "FUNCTION" => self.function()?,
+                        function => return Err(error(
+                                token.line, token.column,
+                                format!("unknown membership function `{}`", function))),
+                    }
+                },
+                _ => return Err(self.parser.unexpected("a membership function")),
+            };
+
+        self.parser.expect_symbol(";")?;
+        Ok((name, term))
+    }
+
+    fn function(&mut self) -> Result<Term, ParseError> {
+        let constant = self.parser.expect_number("a number")?;
+        let mut coefficients = Vec::new();
+        while self.parser.at_symbol("+") {
+            self.parser.next();
+            let coefficient = self.parser.expect_number("a number")?;
+            self.parser.expect_symbol("*")?;
+            let (name, token) = self.parser.expect_ident("an input name")?;
+            let input = self.fuzzy.find_input(&name)
+                .ok_or_else(|| error(token.line, token.column,
+                                     format!("unknown input `{}`", name)))?;
+            coefficients.push((input, coefficient));
+        }
+        Ok(Term::Consequent(Consequent::Linear { coefficients, constant }))
+    }
+
+    fn fuzzify(&mut self) -> Result<(), ParseError> {
+        let (name, token) = self.parser.expect_ident("an input name")?;
+        let input = self.fuzzy.find_input(&name)
+            .ok_or_else(|| error(token.line, token.column,
+                                 format!("unknown input `{}`", name)))?;
+
+        loop {
+            let token = self.parser.peek().clone();
+            match self.keyword("`TERM`, `RANGE` or `END_FUZZIFY`")?.as_str() {
+                "RANGE" => {
+                    let (min, max) = self.range()?;
+                    self.fuzzy.inputs[input.id].min = min;
+                    self.fuzzy.inputs[input.id].max = max;
+                },
+                "TERM" => {
+                    let (term, shape) = self.term()?;
+                    match shape {
+                        Term::Membership(shape) => {
+                            let id = self.fuzzy.add_input_set(input, shape);
+                            self.fuzzy.set_input_set_name(id, &term);
+                        },
+                        Term::Consequent(_) => return Err(error(
+                                token.line, token.column,
+                                format!("input term `{}` must be a membership function", term))),
+                    }
+                },
+                "END_FUZZIFY" => return Ok(()),
+                keyword => return Err(error(token.line, token.column,
+                                            format!("unexpected `{}`", keyword))),
+            }
+        }
+    }
+
+    fn defuzzify(&mut self) -> Result<(), ParseError> {
+        let (name, token) = self.parser.expect_ident("an output name")?;
+        let output = self.fuzzy.find_output(&name)
+            .ok_or_else(|| error(token.line, token.column,
+                                 format!("unknown output `{}`", name)))?;
+        let mut has_range = false;
+        let mut has_terms = false;
+
+        loop {
+            let token = self.parser.peek().clone();
+            match self.keyword("`TERM`, `RANGE`, `METHOD`, `DEFAULT` or `END_DEFUZZIFY`")?.as_str() {
+                "RANGE" => {
+                    let (min, max) = self.range()?;
+                    self.fuzzy.outputs[output.id].min = min;
+                    self.fuzzy.outputs[output.id].max = max;
+                    has_range = true;
+                },
+                "TERM" => {
+                    let (term, value) = self.term()?;
+                    let kind =
+                        match value {
+                            Term::Membership(_) => OutputKind::Mamdani,
+                            Term::Consequent(_) => OutputKind::Sugeno,
+                        };
+                    if !has_terms {
+                        self.fuzzy.outputs[output.id].kind = kind;
+                    }
+                    else if self.fuzzy.outputs[output.id].kind != kind {
+                        return Err(error(token.line, token.column,
+                                         format!("output `{}` mixes singleton and \
+                                                  membership function terms", name)));
+                    }
+                    has_terms = true;
+
+                    let id =
+                        match value {
+                            Term::Membership(shape) => self.fuzzy.add_output_set(output, shape),
+                            Term::Consequent(consequent) =>
+                                self.fuzzy.add_consequent(output, consequent),
+                        };
+                    self.fuzzy.set_output_set_name(id, &term);
+                },
+                "METHOD" => {
+                    self.parser.expect_symbol(":")?;
+                    let token = self.parser.peek().clone();
+                    let method = self.keyword("a defuzzification method")?;
+                    let defuzzifier = parse_method(&method)
+                        .ok_or_else(|| error(token.line, token.column,
+                                             format!("unknown method `{}`", method)))?;
+                    self.fuzzy.set_defuzzifier(output, defuzzifier);
+                    self.parser.expect_symbol(";")?;
+                },
+                "DEFAULT" => {
+                    self.parser.expect_symbol(":=")?;
+                    let fallback =
+                        if self.parser.at_keyword("NC") {
+                            self.parser.next();
+                            Fallback::HoldPrevious
+                        }
+                        else {
+                            Fallback::Default(self.parser.expect_number("a number or `NC`")?)
+                        };
+                    self.fuzzy.set_fallback(output, fallback);
+                    self.parser.expect_symbol(";")?;
+                },
+                "END_DEFUZZIFY" => {
+                    if !has_range && self.fuzzy.outputs[output.id].kind == OutputKind::Mamdani {
+                        return Err(error(token.line, token.column,
+                                         format!("output `{}` has no RANGE", name)));
+                    }
+                    return Ok(());
+                },
+                keyword => return Err(error(token.line, token.column,
+                                            format!("unexpected `{}`", keyword))),
+            }
+        }
+    }
+
+    fn operator<T, F>(&mut self, parse: F) -> Result<T, ParseError>
+        where F: Fn(&str) -> Option<T>
+    {
+        self.parser.expect_symbol(":")?;
+        let token = self.parser.peek().clone();
+        let name = self.keyword("an operator")?;
+        let operator = parse(&name)
+            .ok_or_else(|| error(token.line, token.column,
+                                 format!("unknown operator `{}`", name)))?;
+        self.parser.expect_symbol(";")?;
+        Ok(operator)
+    }
+
+    fn rule_block(&mut self) -> Result<(), ParseError> {
+        let (name, _) = self.parser.expect_ident("a rule block name")?;
+        let mut operators = Operators::default();
+        let mut rules = Vec::new();
+
+        loop {
+            let token = self.parser.peek().clone();
+            match self.keyword("`RULE` or `END_RULEBLOCK`")?.as_str() {
+                "AND" => operators.and = self.operator(parse_and)?,
+                "OR" => operators.or = self.operator(parse_or)?,
+                "ACCU" => operators.accumulation = self.operator(parse_or)?,
+                "ACT" => operators.implication = self.operator(parse_act)?,
+                "RULE" => {
+                    self.parser.next();
+                    self.parser.expect_symbol(":")?;
+                    let rule = self.parser.rule(&self.fuzzy)?;
+                    self.parser.expect_symbol(";")?;
+                    rules.push(self.fuzzy.add_weighted_compound_rule(
+                            rule.antecedent, rule.output_set, rule.weight));
+                },
+                "END_RULEBLOCK" => break,
+                keyword => return Err(error(token.line, token.column,
+                                            format!("unexpected `{}`", keyword))),
+            }
+        }
+
+        let id = self.fuzzy.add_rule_set_with_operators(&rules, operators);
+        self.fuzzy.set_rule_set_name(id, &name);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    const CONTROLLER: &str = "
+        FUNCTION_BLOCK tipper
+
+        VAR_INPUT
+            service : REAL;
+            food : REAL;
+        END_VAR
+
+        VAR_OUTPUT
+            tip : REAL;
+        END_VAR
+
+        FUZZIFY service
+            TERM poor := gauss 0 1.5;
+            TERM good := gauss 5 1.5;
+            TERM excellent := gauss 10 1.5;
+        END_FUZZIFY
+
+        FUZZIFY food
+            RANGE := (0 .. 10);
+            TERM rancid := (0, 1) (1, 1) (3, 0);
+            TERM delicious := (7, 0) (9, 1) (10, 1);
+        END_FUZZIFY
+
+        DEFUZZIFY tip
+            TERM cheap := trian 0 5 10;
+            TERM average := trian 10 15 20;
+            TERM generous := trian 20 25 30;
+            METHOD : COG;
+            RANGE := (0 .. 30);
+        END_DEFUZZIFY
+
+        (* the classic tipping problem *)
+        RULEBLOCK No1
+            AND : MIN;
+            ACT : MIN;
+            ACCU : MAX;
+            RULE 1 : IF service IS poor OR food IS rancid THEN tip IS cheap;
+            RULE 2 : IF service IS good THEN tip IS average;
+            RULE 3 : IF service IS excellent AND food IS delicious THEN tip IS generous WITH 0.5;
+        END_RULEBLOCK
+
+        END_FUNCTION_BLOCK
+    ";
+
+    fn evaluate_tip(fuzzy: &mut Fuzzy, service: f32, food: f32) -> f32 {
+        let rule_set = fuzzy.find_rule_set("No1").unwrap();
+        fuzzy.set_input(fuzzy.find_input("service").unwrap(), service).unwrap();
+        fuzzy.set_input(fuzzy.find_input("food").unwrap(), food).unwrap();
+        fuzzy.evaluate(rule_set).unwrap();
+        fuzzy.get_output(fuzzy.find_output("tip").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_load_fcl() {
+        let mut fuzzy = Fuzzy::from_fcl(CONTROLLER).unwrap();
+        assert_eq!(fuzzy.inputs.len(), 2);
+        assert_eq!(fuzzy.rules.len(), 3);
+        assert_relative_eq!(fuzzy.rules[2].weight, 0.5);
+
+        let tip = evaluate_tip(&mut fuzzy, 0.0, 0.0);
+        assert_relative_eq!(tip, 5.0, epsilon = 0.2);
+        let tip = evaluate_tip(&mut fuzzy, 5.0, 5.0);
+        assert_relative_eq!(tip, 15.0, epsilon = 0.2);
+    }
+
+    #[test]
+    fn test_fcl_round_trip() {
+        let mut fuzzy = Fuzzy::from_fcl(CONTROLLER).unwrap();
+        let text = fuzzy.to_fcl("tipper");
+        let mut loaded = Fuzzy::from_fcl(&text).unwrap();
+        assert_eq!(loaded.to_fcl("tipper"), text);
+
+        for &(service, food) in [(1.0, 2.0), (4.0, 8.0), (9.0, 9.5)].iter() {
+            assert_relative_eq!(
+                evaluate_tip(&mut fuzzy, service, food),
+                evaluate_tip(&mut loaded, service, food));
+        }
+    }
+
+    #[test]
+    fn test_fcl_default() {
+        let text = CONTROLLER.replace("METHOD : COG;", "METHOD : COG;\n DEFAULT := 7.5;");
+        let fuzzy = Fuzzy::from_fcl(&text).unwrap();
+        let tip = fuzzy.find_output("tip").unwrap();
+        assert_eq!(fuzzy.outputs[tip.id].fallback, Fallback::Default(7.5));
+        assert!(fuzzy.to_fcl("tipper").contains("    DEFAULT := 7.5;\n"));
+
+        let text = CONTROLLER.replace("METHOD : COG;", "METHOD : COG;\n DEFAULT := nc;");
+        let fuzzy = Fuzzy::from_fcl(&text).unwrap();
+        assert_eq!(fuzzy.outputs[tip.id].fallback, Fallback::HoldPrevious);
+        assert!(fuzzy.to_fcl("tipper").contains("    DEFAULT := NC;\n"));
+    }
+
+    #[test]
+    fn test_fcl_interval() {
+        let mut fuzzy = Fuzzy::new();
+        let input = fuzzy.add_input(0.0, 10.0);
+        let upper = Shape::Triangle { a: 2.0, b: 5.0, c: 8.0 };
+        let set = fuzzy.add_input_set(input, MembershipFunction::Interval {
+            lower: Shape::Triangle { a: 3.0, b: 5.0, c: 7.0 },
+            upper: upper.clone(),
+        });
+        fuzzy.add_input_set(input, MembershipFunction::Custom(Box::new(|x| x / 10.0)));
+
+        let text = fuzzy.to_fcl("interval");
+        assert!(text.contains(
+            "TERM term0 := trian 2 5 8; (* interval set, lower membership function dropped *)"));
+        assert!(text.contains("; (* sampled from a custom function *)"));
+
+        let loaded = Fuzzy::from_fcl(&text).unwrap();
+        assert_eq!(loaded.input_set_function(set).shape(), Some(&upper));
+    }
+
+    #[test]
+    fn test_fcl_errors() {
+        let text = CONTROLLER.replace("IF service IS good", "IF service IS great");
+        let err = Fuzzy::from_fcl(&text).err().unwrap();
+        assert_eq!((err.line, err.column), (39, 36));
+        assert_eq!(err.message, "unknown term `great` of input `service`");
+
+        let text = CONTROLLER.replace("RANGE := (0 .. 30);", "");
+        let err = Fuzzy::from_fcl(&text).err().unwrap();
+        assert_eq!(err.message, "output `tip` has no RANGE");
+    }
+}
@@ -0,0 +1,651 @@
+use serde::{Serialize, Deserialize, Deserializer};
+use serde::de::Error;
+
+use super::*;
+use super::fcl::sampled_points;
+use super::parser::error;
+
+const VERSION: usize = 1;
+
+// The document mirrors the layout of Fuzzy, entries refer to each other by
+// index. Values that can be checked on their own are checked while reading
+// so that the error points into the text, the references between entries
+// are checked once the whole document is read.
+#[derive(Serialize, Deserialize)]
+struct JsonFuzzy {
+    #[serde(deserialize_with = "version")]
+    version: usize,
+    inputs: Vec<JsonInput>,
+    outputs: Vec<JsonOutput>,
+    input_sets: Vec<JsonInputSet>,
+    output_sets: Vec<JsonOutputSet>,
+    rules: Vec<JsonRule>,
+    rule_sets: Vec<JsonRuleSet>,
+    #[serde(default)]
+    chains: Vec<JsonChain>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonInput {
+    name: String,
+    #[serde(with = "number")]
+    min: f32,
+    #[serde(with = "number")]
+    max: f32,
+    range_policy: RangePolicy,
+    #[serde(with = "number")]
+    value: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonOutput {
+    name: String,
+    #[serde(with = "number")]
+    min: f32,
+    #[serde(with = "number")]
+    max: f32,
+    kind: OutputKind,
+    defuzzifier: Defuzzifier,
+    #[serde(deserialize_with = "resolution")]
+    resolution: usize,
+    fallback: Fallback,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonInputSet {
+    name: String,
+    input: usize,
+    function: JsonFunction,
+}
+
+// Mamdani sets have a function, Sugeno sets a consequent.
+#[derive(Serialize, Deserialize)]
+struct JsonOutputSet {
+    name: String,
+    output: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function: Option<JsonFunction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    consequent: Option<JsonConsequent>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JsonFunction {
+    Triangle { a: f32, b: f32, c: f32 },
+    Trapezoid { a: f32, b: f32, c: f32, d: f32 },
+    LeftShoulder { a: f32, b: f32 },
+    RightShoulder { a: f32, b: f32 },
+    Gaussian { mean: f32, sigma: f32 },
+    Bell { a: f32, b: f32, c: f32 },
+    Sigmoid { slope: f32, center: f32 },
+    PiecewiseLinear {
+        #[serde(deserialize_with = "points")]
+        points: Vec<(f32, f32)>,
+    },
+    Interval { lower: Box<JsonFunction>, upper: Box<JsonFunction> },
+}
+
+// A rule condition is an input set index or an object holding a single
+// operator.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum JsonAntecedent {
+    Is(usize),
+    Operator(JsonOperator),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JsonOperator {
+    And(Vec<JsonAntecedent>),
+    Or(Vec<JsonAntecedent>),
+    Not(Box<JsonAntecedent>),
+    Very(Box<JsonAntecedent>),
+    Somewhat(Box<JsonAntecedent>),
+    Extremely(Box<JsonAntecedent>),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum JsonConsequent {
+    Constant(f32),
+    Linear { constant: f32, coefficients: Vec<(usize, f32)> },
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonRule {
+    #[serde(rename = "if")]
+    antecedent: JsonAntecedent,
+    then: usize,
+    #[serde(deserialize_with = "weight")]
+    weight: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonRuleSet {
+    name: String,
+    and: TNorm,
+    or: SNorm,
+    accumulation: SNorm,
+    #[serde(default = "minimum")]
+    implication: Implication,
+    rules: Vec<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonChain {
+    name: String,
+    rule_sets: Vec<usize>,
+    links: Vec<(usize, usize)>,
+}
+
+fn version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    let version = usize::deserialize(deserializer)?;
+    if version != VERSION {
+        return Err(D::Error::custom(format!("unsupported version {}", version)));
+    }
+    Ok(version)
+}
+
+fn resolution<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    let resolution = usize::deserialize(deserializer)?;
+    if resolution == 0 {
+        return Err(D::Error::custom("resolution must be positive"));
+    }
+    Ok(resolution)
+}
+
+fn weight<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let weight = f32::deserialize(deserializer)?;
+    if !(0.0..=1.0).contains(&weight) {
+        return Err(D::Error::custom("weight must be in [0, 1]"));
+    }
+    Ok(weight)
+}
+
+fn points<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<(f32, f32)>, D::Error> {
+    let points = Vec::<(f32, f32)>::deserialize(deserializer)?;
+    if points.is_empty() {
+        return Err(D::Error::custom("expected at least one point"));
+    }
+    if !points.windows(2).all(|w| w[0].0 <= w[1].0) {
+        return Err(D::Error::custom("points must be in ascending order of x"));
+    }
+    Ok(points)
+}
+
+fn minimum() -> Implication {
+    Implication::Minimum
+}
+
+// Infinite and NaN values have no JSON literal and are written as strings.
+mod number {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de::Error;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Number {
+        Number(f32),
+        String(String),
+    }
+
+    pub fn serialize<S: Serializer>(x: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+        if x.is_finite() {
+            serializer.serialize_f32(*x)
+        }
+        else {
+            serializer.collect_str(x)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+        match Number::deserialize(deserializer)? {
+            Number::Number(x) => Ok(x),
+            Number::String(s) => s.parse()
+                .map_err(|_| D::Error::custom(format!("expected a number, found `{}`", s))),
+        }
+    }
+}
+
+// Errors found after reading have no position in the text and name the
+// entry they were found in instead.
+fn entry_error(list: &'static str, index: usize) -> impl Fn(String) -> ParseError {
+    move |message| error(0, 0, format!("{}[{}]: {}", list, index, message))
+}
+
+fn index(id: usize, count: usize, what: &str) -> Result<usize, String> {
+    if id >= count {
+        return Err(format!("unknown {} {}", what, id));
+    }
+    Ok(id)
+}
+
+fn json_shape(shape: &Shape) -> JsonFunction {
+    use Shape::*;
+
+    match *shape {
+        Triangle { a, b, c } => JsonFunction::Triangle { a, b, c },
+        Trapezoid { a, b, c, d } => JsonFunction::Trapezoid { a, b, c, d },
+        LeftShoulder { a, b } => JsonFunction::LeftShoulder { a, b },
+        RightShoulder { a, b } => JsonFunction::RightShoulder { a, b },
+        Gaussian { mean, sigma } => JsonFunction::Gaussian { mean, sigma },
+        Bell { a, b, c } => JsonFunction::Bell { a, b, c },
+        Sigmoid { slope, center } => JsonFunction::Sigmoid { slope, center },
+        PiecewiseLinear(ref points) => JsonFunction::PiecewiseLinear { points: points.clone() },
+    }
+}
+
+// Custom functions can't be written out and are sampled over the range of
+// their variable, as in the FCL export.
+fn json_function(f: &MembershipFunction, min: f32, max: f32) -> JsonFunction {
+    match f {
+        MembershipFunction::Shape(shape) => json_shape(shape),
+        MembershipFunction::Interval { lower, upper } => JsonFunction::Interval {
+            lower: Box::new(json_shape(lower)),
+            upper: Box::new(json_shape(upper)),
+        },
+        MembershipFunction::Custom(_) =>
+            JsonFunction::PiecewiseLinear { points: sampled_points(f, min, max) },
+    }
+}
+
+fn read_shape(f: JsonFunction) -> Result<Shape, String> {
+    use JsonFunction::*;
+
+    let shape =
+        match f {
+            Triangle { a, b, c } => Shape::Triangle { a, b, c },
+            Trapezoid { a, b, c, d } => Shape::Trapezoid { a, b, c, d },
+            LeftShoulder { a, b } => Shape::LeftShoulder { a, b },
+            RightShoulder { a, b } => Shape::RightShoulder { a, b },
+            Gaussian { mean, sigma } => Shape::Gaussian { mean, sigma },
+            Bell { a, b, c } => Shape::Bell { a, b, c },
+            Sigmoid { slope, center } => Shape::Sigmoid { slope, center },
+            PiecewiseLinear { points } => Shape::PiecewiseLinear(points),
+            Interval { .. } =>
+                return Err("the bounds of an interval set must be shapes".to_string()),
+        };
+    Ok(shape)
+}
+
+fn read_function(f: JsonFunction) -> Result<MembershipFunction, String> {
+    match f {
+        JsonFunction::Interval { lower, upper } => Ok(MembershipFunction::Interval {
+            lower: read_shape(*lower)?,
+            upper: read_shape(*upper)?,
+        }),
+        f => Ok(MembershipFunction::Shape(read_shape(f)?)),
+    }
+}
+
+fn json_antecedent(antecedent: &Antecedent) -> JsonAntecedent {
+    let list = |antecedents: &[Antecedent]| antecedents.iter().map(json_antecedent).collect();
+    let operator =
+        match antecedent {
+            Antecedent::Is(input_set) => return JsonAntecedent::Is(input_set.id),
+            Antecedent::And(antecedents) => JsonOperator::And(list(antecedents)),
+            Antecedent::Or(antecedents) => JsonOperator::Or(list(antecedents)),
+            Antecedent::Not(antecedent) => JsonOperator::Not(Box::new(json_antecedent(antecedent))),
+            Antecedent::Hedge(hedge, antecedent) => {
+                let antecedent = Box::new(json_antecedent(antecedent));
+                match hedge {
+                    Hedge::Very => JsonOperator::Very(antecedent),
+                    Hedge::Somewhat => JsonOperator::Somewhat(antecedent),
+                    Hedge::Extremely => JsonOperator::Extremely(antecedent),
+                }
+            },
+        };
+    JsonAntecedent::Operator(operator)
+}
+
+fn read_antecedent(antecedent: JsonAntecedent, input_sets: usize) -> Result<Antecedent, String> {
+    let read = |antecedent: Box<JsonAntecedent>| read_antecedent(*antecedent, input_sets);
+    let list = |antecedents: Vec<JsonAntecedent>| antecedents.into_iter()
+        .map(|antecedent| read_antecedent(antecedent, input_sets))
+        .collect::<Result<Vec<_>, _>>();
+    let operator =
+        match antecedent {
+            JsonAntecedent::Is(id) =>
+                return Ok(Antecedent::is(InputSetId { id: index(id, input_sets, "input set")? })),
+            JsonAntecedent::Operator(operator) => operator,
+        };
+    let antecedent =
+        match operator {
+            JsonOperator::And(antecedents) => Antecedent::And(list(antecedents)?),
+            JsonOperator::Or(antecedents) => Antecedent::Or(list(antecedents)?),
+            JsonOperator::Not(antecedent) => !read(antecedent)?,
+            JsonOperator::Very(antecedent) => read(antecedent)?.very(),
+            JsonOperator::Somewhat(antecedent) => read(antecedent)?.somewhat(),
+            JsonOperator::Extremely(antecedent) => read(antecedent)?.extremely(),
+        };
+    Ok(antecedent)
+}
+
+fn json_consequent(consequent: &Consequent) -> JsonConsequent {
+    match consequent {
+        Consequent::Constant(value) => JsonConsequent::Constant(*value),
+        Consequent::Linear { coefficients, constant } => JsonConsequent::Linear {
+            constant: *constant,
+            coefficients: coefficients.iter()
+                .map(|&(input, coefficient)| (input.id, coefficient))
+                .collect(),
+        },
+    }
+}
+
+fn read_consequent(consequent: JsonConsequent, inputs: usize) -> Result<Consequent, String> {
+    match consequent {
+        JsonConsequent::Constant(value) => Ok(Consequent::Constant(value)),
+        JsonConsequent::Linear { constant, coefficients } => {
+            let coefficients = coefficients.into_iter()
+                .map(|(input, coefficient)|
+                    Ok((InputId { id: index(input, inputs, "input")? }, coefficient)))
+                .collect::<Result<_, String>>()?;
+            Ok(Consequent::Linear { coefficients, constant })
+        },
+    }
+}
+
+impl Fuzzy {
+    pub fn to_json(&self) -> String {
+        let inputs = self.inputs.iter()
+            .map(|input| JsonInput {
+                name: input.name.clone(),
+                min: input.min,
+                max: input.max,
+                range_policy: input.range_policy,
+                value: input.value,
+            })
+            .collect();
+        let outputs = self.outputs.iter()
+            .map(|output| JsonOutput {
+                name: output.name.clone(),
+                min: output.min,
+                max: output.max,
+                kind: output.kind,
+                defuzzifier: output.defuzzifier,
+                resolution: output.resolution,
+                fallback: output.fallback,
+            })
+            .collect();
+        let input_sets = self.input_sets.iter()
+            .map(|set| {
+                let input = &self.inputs[set.input.id];
+                JsonInputSet {
+                    name: set.name.clone(),
+                    input: set.input.id,
+                    function: json_function(&set.f, input.min, input.max),
+                }
+            })
+            .collect();
+        let output_sets = self.output_sets.iter()
+            .map(|set| {
+                let output = &self.outputs[set.output.id];
+                let (function, consequent) =
+                    match set.term {
+                        OutputTerm::Membership(ref f) =>
+                            (Some(json_function(f, output.min, output.max)), None),
+                        OutputTerm::Consequent(ref consequent) =>
+                            (None, Some(json_consequent(consequent))),
+                    };
+                JsonOutputSet { name: set.name.clone(), output: set.output.id, function, consequent }
+            })
+            .collect();
+        let rules = self.rules.iter()
+            .map(|rule| JsonRule {
+                antecedent: json_antecedent(&rule.antecedent),
+                then: rule.output_set.id,
+                weight: rule.weight,
+            })
+            .collect();
+        let rule_sets = self.rule_sets.iter()
+            .map(|rule_set| JsonRuleSet {
+                name: rule_set.name.clone(),
+                and: rule_set.operators.and,
+                or: rule_set.operators.or,
+                accumulation: rule_set.operators.accumulation,
+                implication: rule_set.operators.implication,
+                rules: rule_set.rules.iter().map(|rule| rule.id).collect(),
+            })
+            .collect();
+        let chains = self.chains.iter()
+            .map(|chain| JsonChain {
+                name: chain.name.clone(),
+                rule_sets: chain.rule_sets.iter().map(|rule_set| rule_set.id).collect(),
+                links: chain.links.iter().map(|link| (link.output.id, link.input.id)).collect(),
+            })
+            .collect();
+
+        let document = JsonFuzzy {
+            version: VERSION,
+            inputs,
+            outputs,
+            input_sets,
+            output_sets,
+            rules,
+            rule_sets,
+            chains,
+        };
+        let mut text = serde_json::to_string_pretty(&document).unwrap();
+        text.push('\n');
+        text
+    }
+
+    pub fn from_json(text: &str) -> Result<Fuzzy, ParseError> {
+        let document: JsonFuzzy = serde_json::from_str(text).map_err(|err| {
+            let message = err.to_string();
+            let position = format!(" at line {} column {}", err.line(), err.column());
+            let message = message.strip_suffix(&position).unwrap_or(&message);
+            error(err.line(), err.column(), message.to_string())
+        })?;
+
+        let mut fuzzy = Fuzzy::new();
+        for input in document.inputs {
+            fuzzy.inputs.push(Input {
+                name: input.name,
+                min: input.min,
+                max: input.max,
+                range_policy: input.range_policy,
+                value: input.value,
+            });
+        }
+        for output in document.outputs {
+            fuzzy.outputs.push(Output {
+                name: output.name,
+                min: output.min,
+                max: output.max,
+                kind: output.kind,
+                defuzzifier: output.defuzzifier,
+                resolution: output.resolution,
+                fallback: output.fallback,
+            });
+        }
+        for (i, set) in document.input_sets.into_iter().enumerate() {
+            let error = entry_error("input_sets", i);
+            let input = index(set.input, fuzzy.inputs.len(), "input").map_err(&error)?;
+            fuzzy.input_sets.push(InputSet {
+                name: set.name,
+                input: InputId { id: input },
+                f: read_function(set.function).map_err(&error)?,
+            });
+        }
+        for (i, set) in document.output_sets.into_iter().enumerate() {
+            let error = entry_error("output_sets", i);
+            let output = index(set.output, fuzzy.outputs.len(), "output").map_err(&error)?;
+            let term =
+                match (fuzzy.outputs[output].kind, set.function, set.consequent) {
+                    (OutputKind::Mamdani, Some(f), _) =>
+                        OutputTerm::Membership(read_function(f).map_err(&error)?),
+                    (OutputKind::Sugeno, _, Some(consequent)) => OutputTerm::Consequent(
+                        read_consequent(consequent, fuzzy.inputs.len()).map_err(&error)?),
+                    (OutputKind::Mamdani, _, _) =>
+                        return Err(error("missing field `function`".to_string())),
+                    (OutputKind::Sugeno, _, _) =>
+                        return Err(error("missing field `consequent`".to_string())),
+                };
+            fuzzy.output_sets.push(OutputSet {
+                name: set.name,
+                output: OutputId { id: output },
+                term,
+            });
+        }
+        for (i, rule) in document.rules.into_iter().enumerate() {
+            let error = entry_error("rules", i);
+            let output_set = index(rule.then, fuzzy.output_sets.len(), "output set").map_err(&error)?;
+            fuzzy.rules.push(Rule {
+                antecedent: read_antecedent(rule.antecedent, fuzzy.input_sets.len())
+                    .map_err(&error)?,
+                output_set: OutputSetId { id: output_set },
+                weight: rule.weight,
+            });
+        }
+        for (i, rule_set) in document.rule_sets.into_iter().enumerate() {
+            let rules = rule_set.rules.into_iter()
+                .map(|rule| Ok(RuleId { id: index(rule, fuzzy.rules.len(), "rule")? }))
+                .collect::<Result<_, String>>()
+                .map_err(entry_error("rule_sets", i))?;
+            fuzzy.rule_sets.push(RuleSet {
+                name: rule_set.name,
+                rules,
+                operators: Operators {
+                    and: rule_set.and,
+                    or: rule_set.or,
+                    accumulation: rule_set.accumulation,
+                    implication: rule_set.implication,
+                },
+            });
+        }
+        for (i, chain) in document.chains.into_iter().enumerate() {
+            let error = entry_error("chains", i);
+            let rule_sets: Vec<RuleSetId> = chain.rule_sets.into_iter()
+                .map(|rule_set| Ok(RuleSetId {
+                    id: index(rule_set, fuzzy.rule_sets.len(), "rule set")?,
+                }))
+                .collect::<Result<_, String>>()
+                .map_err(&error)?;
+            let links: Vec<Link> = chain.links.into_iter()
+                .map(|(output, input)| Ok(Link {
+                    output: OutputId { id: index(output, fuzzy.outputs.len(), "output")? },
+                    input: InputId { id: index(input, fuzzy.inputs.len(), "input")? },
+                }))
+                .collect::<Result<_, String>>()
+                .map_err(&error)?;
+            let id = fuzzy.add_chain(&rule_sets, &links).map_err(|err| error(err.to_string()))?;
+            fuzzy.set_chain_name(id, &chain.name);
+        }
+        Ok(fuzzy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    fn complete_fuzzy() -> (Fuzzy, Vec<RuleSetId>) {
+        let mut fuzzy = Fuzzy::new();
+        let x = fuzzy.add_input(0.0, 10.0);
+        let y = fuzzy.add_input(f32::NEG_INFINITY, f32::INFINITY);
+        fuzzy.set_input_name(x, "x \"quoted\"");
+        fuzzy.set_range_policy(x, RangePolicy::Clamp).unwrap();
+        fuzzy.set_input(y, 0.3).unwrap();
+
+        let low = fuzzy.add_input_set(x, Shape::LeftShoulder { a: 2.0, b: 6.0 });
+        let high = fuzzy.add_input_set(x, MembershipFunction::interval_around(
+                Shape::Triangle { a: 4.0, b: 7.5, c: 10.0 }, 0.5));
+        let wave = fuzzy.add_input_set(x, Box::new(|x: f32| 0.5 + 0.5 * (x / 3.0).sin()));
+        let small = fuzzy.add_input_set(y, Shape::Gaussian { mean: 0.0, sigma: 0.4 });
+
+        let speed = fuzzy.add_output(0.0, 1.0);
+        fuzzy.set_defuzzifier(speed, Defuzzifier::Bisector);
+        fuzzy.set_resolution(speed, 64);
+        fuzzy.set_fallback(speed, Fallback::Default(0.25));
+        let slow = fuzzy.add_output_set(speed, Shape::PiecewiseLinear(
+                vec![(0.0, 1.0), (0.3, 1.0), (0.6, 0.0)]));
+        let fast = fuzzy.add_output_set(speed, Shape::Sigmoid { slope: 12.0, center: 0.6 });
+
+        let turn = fuzzy.add_sugeno_output(-1.0, 1.0);
+        fuzzy.set_fallback(turn, Fallback::HoldPrevious);
+        let left = fuzzy.add_consequent(turn, Consequent::Constant(-0.7));
+        let along = fuzzy.add_consequent(turn, Consequent::Linear {
+            coefficients: vec![(x, 0.1), (y, -2.0)],
+            constant: 0.1,
+        });
+
+        let r1 = fuzzy.add_weighted_compound_rule(Antecedent::all(&[low, small]), slow, 0.8);
+        let r2 = fuzzy.add_compound_rule(
+            Antecedent::is(high).very().or(Antecedent::Not(Box::new(Antecedent::is(wave)))),
+            fast);
+        let r3 = fuzzy.add_rule(&[low], left);
+        let r4 = fuzzy.add_compound_rule(Antecedent::is(high).somewhat(), along);
+        let operators = Operators {
+            and: TNorm::Product,
+            or: SNorm::ProbabilisticSum,
+            accumulation: SNorm::BoundedSum,
+            implication: Implication::Product,
+        };
+        let mamdani = fuzzy.add_rule_set_with_operators(&[r1, r2], operators);
+        let sugeno = fuzzy.add_rule_set(&[r3, r4, r1]);
+        fuzzy.set_rule_set_name(sugeno, "steer");
+        (fuzzy, vec![mamdani, sugeno])
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let (fuzzy, rule_sets) = complete_fuzzy();
+        let text = fuzzy.to_json();
+        let loaded = Fuzzy::from_json(&text).unwrap();
+        assert_eq!(loaded.to_json(), text);
+        assert_eq!(loaded.rule_set_name(rule_sets[1]), "steer");
+        assert_eq!(loaded.input_values(), vec![0.0, 0.3]);
+
+        let mut scratch = Scratch::default();
+        let mut loaded_scratch = Scratch::default();
+        for &inputs in [[1.0, 0.1], [5.0, -0.2], [8.5, 0.6], [12.0, 0.0]].iter() {
+            for &rule_set in rule_sets.iter() {
+                let expected = fuzzy.evaluate_with(rule_set, &inputs, &mut scratch).unwrap();
+                let found = loaded.evaluate_with(rule_set, &inputs, &mut loaded_scratch).unwrap();
+                for output in 0..2 {
+                    let output = OutputId { id: output };
+                    match (expected.value(output), found.value(output)) {
+                        (Some(a), Some(b)) => assert_relative_eq!(a, b, epsilon = 1e-6),
+                        (a, b) => assert_eq!(a, b),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_json_errors() {
+        let (fuzzy, _) = complete_fuzzy();
+        let text = fuzzy.to_json();
+        let message = |text: &str| Fuzzy::from_json(text).unwrap_err().to_string();
+
+        assert_eq!(message("{\"version\": 2}"), "1:14: unsupported version 2");
+        assert_eq!(message("{\"version\": 1,\n \"inputs\": [}"), "2:13: expected value");
+        assert_eq!(message(&text.replacen("\"then\": 0", "\"then\": 9", 1)),
+                   "rules[0]: unknown output set 9");
+        assert!(message(&text.replacen("\"type\": \"gaussian\"", "\"type\": \"cone\"", 1))
+                .split(": ").nth(1).unwrap().starts_with("unknown variant `cone`"));
+        assert!(message(&text.replacen("\"and\": \"product\"", "\"and\": \"max\"", 1))
+                .split(": ").nth(1).unwrap().starts_with("unknown variant `max`"));
+        assert_eq!(message(&text.replacen("\"weight\": 0.8", "\"weight\": 1.5", 1))
+                   .split(": ").nth(1), Some("weight must be in [0, 1]"));
+        assert_eq!(message(&text.replacen("\"range_policy\": \"clamp\",", "", 1))
+                   .split(": ").nth(1), Some("missing field `range_policy`"));
+
+        for &points in [&[][..], &[(0.5, 1.0), (0.2, 0.0)][..]].iter() {
+            let (mut fuzzy, _) = complete_fuzzy();
+            let speed = fuzzy.add_output(0.0, 1.0);
+            fuzzy.add_output_set(speed, Shape::PiecewiseLinear(points.to_vec()));
+            let expected =
+                if points.is_empty() { "expected at least one point" }
+                else { "points must be in ascending order of x" };
+            assert_eq!(message(&fuzzy.to_json()).split(": ").nth(1), Some(expected));
+        }
+    }
+}
@@ -1,6 +1,144 @@
 use super::*;
 
-const INTEGRAL_STEP: usize = 40;
+const MAXIMUM_EPSILON: f32 = 1e-6;
+
+#[derive(Clone, Debug, Default)]
+pub struct Scratch {
+    rule_set: Option<RuleSetId>,
+    inputs: Vec<f32>,
+    input_memberships: Vec<f32>,
+    lower_input_memberships: Vec<f32>,
+    output_memberships: Vec<Option<f32>>,
+    lower_output_memberships: Vec<Option<f32>>,
+    active_output_sets: Vec<Vec<OutputSetId>>,
+    sugeno_sums: Vec<(f32, f32)>,
+    sugeno_firings: Vec<Vec<(f32, f32, f32)>>,
+    values: Vec<f32>,
+    intervals: Vec<(f32, f32)>,
+    held: Vec<f32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Evaluation {
+    values: Vec<f32>,
+    intervals: Vec<(f32, f32)>,
+    fallbacks: Vec<OutputId>,
+}
+
+#[derive(Copy, Clone)]
+pub(super) struct Activation<'a> {
+    output: &'a Output,
+    output_sets: &'a [OutputSet],
+    active_output_sets: &'a [OutputSetId],
+    output_memberships: &'a [Option<f32>],
+    lower_output_memberships: &'a [Option<f32>],
+    implication: Implication,
+}
+
+impl Scratch {
+    fn prepare(&mut self, fuzzy: &Fuzzy) {
+        self.input_memberships.resize(fuzzy.input_sets.len(), 0.0);
+        self.lower_input_memberships.resize(fuzzy.input_sets.len(), 0.0);
+        self.output_memberships.resize(fuzzy.output_sets.len(), None);
+        self.lower_output_memberships.resize(fuzzy.output_sets.len(), None);
+        self.active_output_sets.resize(fuzzy.outputs.len(), Vec::new());
+        self.sugeno_sums.resize(fuzzy.outputs.len(), (0.0, 0.0));
+        self.sugeno_firings.resize(fuzzy.outputs.len(), Vec::new());
+        self.values.resize(fuzzy.outputs.len(), 0.0);
+        self.intervals.resize(fuzzy.outputs.len(), (0.0, 0.0));
+        self.held.resize(fuzzy.outputs.len(), 0.0);
+    }
+
+    // Forgets what HoldPrevious outputs held, as in a new scratch.
+    pub(super) fn forget_held(&mut self) {
+        self.held.clear();
+    }
+
+    pub(super) fn rule_set(&self) -> Option<RuleSetId> {
+        self.rule_set
+    }
+
+    pub(super) fn input_value(&self, id: InputId) -> f32 {
+        self.inputs.get(id.id).cloned().unwrap_or(0.0)
+    }
+
+    pub(super) fn input_memberships(&self) -> &[f32] {
+        &self.input_memberships
+    }
+
+    pub(super) fn input_set_membership(&self, id: InputSetId) -> f32 {
+        self.input_memberships.get(id.id).cloned().unwrap_or(0.0)
+    }
+
+    pub(super) fn output_value(&self, id: OutputId) -> f32 {
+        self.values.get(id.id).cloned().unwrap_or(0.0)
+    }
+
+    pub(super) fn is_active(&self, id: OutputId) -> bool {
+        self.active_output_sets.get(id.id).is_some_and(|sets| !sets.is_empty())
+    }
+}
+
+impl Evaluation {
+    pub fn value(&self, output: OutputId) -> Option<f32> {
+        self.values.get(output.id).cloned().filter(|value| !value.is_nan())
+    }
+
+    pub fn interval(&self, output: OutputId) -> Option<(f32, f32)> {
+        self.intervals.get(output.id).cloned()
+            .filter(|(lower, upper)| !lower.is_nan() && !upper.is_nan())
+    }
+
+    pub fn fell_back(&self, output: OutputId) -> bool {
+        self.fallbacks.contains(&output)
+    }
+
+    // Takes over one output from the evaluation of another rule set.
+    pub(super) fn merge(&mut self, other: &Evaluation, output: OutputId) {
+        let len = other.values.len();
+        if self.values.len() < len {
+            self.values.resize(len, f32::NAN);
+            self.intervals.resize(len, (f32::NAN, f32::NAN));
+        }
+        self.values[output.id] = other.values[output.id];
+        self.intervals[output.id] = other.intervals[output.id];
+        self.fallbacks.retain(|&id| id != output);
+        if other.fell_back(output) {
+            self.fallbacks.push(output);
+        }
+    }
+}
+
+impl<'a> Activation<'a> {
+    pub(super) fn new(fuzzy: &'a Fuzzy, scratch: &'a Scratch, output: OutputId) -> Self {
+        Activation {
+            output: &fuzzy.outputs[output.id],
+            output_sets: &fuzzy.output_sets,
+            active_output_sets: &scratch.active_output_sets[output.id],
+            output_memberships: &scratch.output_memberships,
+            lower_output_memberships: &scratch.lower_output_memberships,
+            implication: scratch.rule_set.map_or(Implication::Minimum, |rule_set| {
+                fuzzy.rule_sets[rule_set.id].operators.implication
+            }),
+        }
+    }
+
+    fn sets(self) -> impl Iterator<Item = (&'a OutputSet, f32)> {
+        let output_sets = self.output_sets;
+        let output_memberships = self.output_memberships;
+        self.active_output_sets.iter()
+            .map(move |id| (&output_sets[id.id], output_memberships[id.id].unwrap_or(0.0)))
+    }
+
+    fn interval_sets(self) -> impl Iterator<Item = (&'a OutputSet, f32, f32)> {
+        let lower_output_memberships = self.lower_output_memberships;
+        self.active_output_sets.iter()
+            .zip(self.sets())
+            .map(move |(id, (output_set, upper))| {
+                (output_set, lower_output_memberships[id.id].unwrap_or(0.0), upper)
+            })
+    }
+}
 
 fn integral(x1: f32, x2: f32, y1: f32, y2: f32) -> f32 {
     let mut result = 
@@ -10,30 +148,70 @@ fn integral(x1: f32, x2: f32, y1: f32, y2: f32) -> f32 {
     result
 }
 
-fn compute_input_membership(
-    rules: &Vec<Rule>,
-    input_sets: &Vec<InputSet>,
-    rule: RuleId) 
+fn antecedent_membership(
+    antecedent: &Antecedent,
+    memberships: &[f32],
+    operators: Operators)
+    -> f32
+{
+    match antecedent {
+        Antecedent::Is(input_set) => memberships[input_set.id],
+        Antecedent::And(terms) => {
+            terms.iter().fold(1.0, |result, term| {
+                operators.and.apply(
+                    result,
+                    antecedent_membership(term, memberships, operators))
+            })
+        },
+        Antecedent::Or(terms) => {
+            terms.iter().fold(0.0, |result, term| {
+                operators.or.apply(
+                    result,
+                    antecedent_membership(term, memberships, operators))
+            })
+        },
+        Antecedent::Not(term) =>
+            1.0 - antecedent_membership(term, memberships, operators),
+        Antecedent::Hedge(hedge, term) =>
+            hedge.apply(antecedent_membership(term, memberships, operators)),
+    }
+}
+
+pub(super) fn compute_input_membership(
+    rules: &[Rule],
+    memberships: &[f32],
+    rule: RuleId,
+    operators: Operators) 
     -> f32 
 {
-    let mut result = 1.0;
-    for input_set in rules[rule.id].input_sets.iter() {
-        result = f32::min(result, input_sets[input_set.id].membership);
+    let rule = &rules[rule.id];
+    antecedent_membership(&rule.antecedent, memberships, operators) * rule.weight
+}
+
+impl Consequent {
+    fn eval(&self, inputs: &[f32]) -> f32 {
+        match self {
+            Consequent::Constant(value) => *value,
+            Consequent::Linear { coefficients, constant } => {
+                coefficients.iter()
+                    .fold(*constant, |sum, &(input, coefficient)| {
+                        sum + coefficient * inputs[input.id]
+                    })
+            },
+        }
     }
-    result
 }
 
-fn collect_dirty_input_sets(
+pub(super) fn collect_dirty_input_sets(
     rule_sets: &Vec<RuleSet>,
     rules: &Vec<Rule>,
     rule_set: RuleSetId) 
     -> Vec<InputSetId> 
 {
-    let mut dirty_input_sets: Vec<InputSetId> =
-        rule_sets[rule_set.id].rules.iter()
-            .flat_map(|rule| rules[rule.id].input_sets.iter())
-            .map(|item| *item)
-            .collect();
+    let mut dirty_input_sets: Vec<InputSetId> = Vec::new();
+    for rule in rule_sets[rule_set.id].rules.iter() {
+        rules[rule.id].antecedent.collect_input_sets(&mut dirty_input_sets);
+    }
 
     use std::cmp::Ordering;
 
@@ -56,125 +234,553 @@ fn collect_dirty_input_sets(
 }
 
 fn output_fuzzy_function(
-    output_sets: &Vec<OutputSet>,
-    output: &Output, x: f32)
+    activation: Activation,
+    x: f32,
+    accumulation: SNorm)
     -> f32
 {
-    let mut max = 0.0;
-    for output_set in output.cached_output_sets.iter() {
-        let output_set_result = f32::min(
-            output_sets[output_set.id].input_membership.unwrap_or(0.0),
-            (output_sets[output_set.id].f)(x)
-        );
-        max = f32::max(max, output_set_result);
+    let mut result = 0.0;
+    for (output_set, level) in activation.sets() {
+        let membership = output_set.term.membership()
+            .map_or(0.0, |f| f.eval(x));
+        let output_set_result = activation.implication.apply(level, membership);
+        result = accumulation.apply(result, output_set_result);
     }
-    max
+    result
 }
 
-fn defuzzificate(
-    output_sets: &Vec<OutputSet>,
-    output: &Output) 
-    -> f32 
+pub(super) fn sample_output(
+    activation: Activation,
+    accumulation: SNorm)
+    -> Vec<(f32, f32)>
 {
+    let output = activation.output;
     let min = output.min;
     let max = output.max;
+    let steps = output.resolution;
 
-    let mut x1: f32 = min;
+    (0..=steps)
+        .map(|i| {
+            let x = i as f32 * (max - min) / (steps as f32) + min;
+            (x, output_fuzzy_function(activation, x, accumulation))
+        })
+        .collect()
+}
 
+fn centroid(points: &[(f32, f32)]) -> f32 {
     let mut nominator: f32 = 0.0;
     let mut denominator: f32 = 0.0;
 
-    for i in 0..INTEGRAL_STEP {
-        let x2 = (i + 1) as f32 * (max - min) / (INTEGRAL_STEP as f32) + min;
-
-        let y1 = output_fuzzy_function(
-            output_sets, output, x1);
-        let y2 = output_fuzzy_function(
-            output_sets, output, x2);
+    for window in points.windows(2) {
+        let (x1, y1) = window[0];
+        let (x2, y2) = window[1];
 
         nominator += integral(x1, x2, y1, y2);
         denominator += (x2 - x1) * (y1 + y2) / 2.0;
+    }
 
-        x1 = x2;
+    nominator / denominator
+}
+
+// The implied membership of the set over [output.min, output.max] as a
+// polyline. Segments crossing a bound of the output are cut there, so the
+// part inside the range is kept.
+fn implied_points(
+    output_set: &OutputSet,
+    output: &Output,
+    level: f32,
+    implication: Implication)
+    -> Option<Vec<(f32, f32)>>
+{
+    let shape = output_set.term.membership()?.shape()?;
+    let mut points = shape.linear_points()?;
+    if points.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let (first_x, first_y) = points[0];
+    let (last_x, last_y) = points[points.len() - 1];
+    if output.min < first_x {
+        points.insert(0, (output.min, first_y));
+    }
+    if output.max > last_x {
+        points.push((output.max, last_y));
+    }
+
+    let mut result = Vec::with_capacity(points.len() * 2 + 2);
+    let mut push = |x: f32, y: f32| {
+        if x >= output.min && x <= output.max {
+            result.push((x, implication.apply(level, y)));
+        }
+    };
+
+    for (i, &(x2, y2)) in points.iter().enumerate() {
+        if i > 0 {
+            let (x1, y1) = points[i - 1];
+            let mut cuts = [output.min, output.max, f32::INFINITY];
+            let clipped = implication == Implication::Minimum;
+            if clipped && (y1 - level) * (y2 - level) < 0.0 {
+                cuts[2] = x1 + (level - y1) * (x2 - x1) / (y2 - y1);
+                cuts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            }
+            for &x in cuts.iter().filter(|&&x| x1 < x && x < x2) {
+                push(x, y1 + (x - x1) * (y2 - y1) / (x2 - x1));
+            }
+        }
+        push(x2, y2);
+    }
+
+    Some(result)
+}
+
+fn envelope_integral(
+    x1: f32, x2: f32,
+    lines: &[(f32, f32)], depth: usize,
+    nominator: &mut f32, denominator: &mut f32)
+{
+    let top = |ys: &dyn Fn(&(f32, f32)) -> f32| {
+        (0..lines.len()).fold(0, |best, i| if ys(&lines[i]) > ys(&lines[best]) { i } else { best })
+    };
+    let top1 = top(&|line| line.0);
+    let top2 = top(&|line| line.1);
+
+    let (a1, b1) = lines[top1];
+    let (a2, b2) = lines[top2];
+    let t = (a1 - a2) / ((a1 - a2) - (b1 - b2));
+
+    if top1 == top2 || depth == 0 || !(t > 0.0 && t < 1.0) {
+        *nominator += integral(x1, x2, a1, b2);
+        *denominator += (x2 - x1) * (a1 + b2) / 2.0;
+        return;
+    }
+
+    let x = x1 + t * (x2 - x1);
+    let left: Vec<(f32, f32)> = lines.iter().map(|&(a, b)| (a, a + t * (b - a))).collect();
+    let right: Vec<(f32, f32)> = left.iter().zip(lines.iter()).map(|(&(_, m), &(_, b))| (m, b)).collect();
+    envelope_integral(x1, x, &left, depth - 1, nominator, denominator);
+    envelope_integral(x, x2, &right, depth - 1, nominator, denominator);
+}
+
+fn exact_centroid(activation: Activation) -> Option<f32> {
+    let mut sets: Vec<Vec<(f32, f32)>> = Vec::new();
+    for (output_set, level) in activation.sets() {
+        let points = implied_points(output_set, activation.output, level, activation.implication)?;
+        if level > 0.0 {
+            sets.push(points);
+        }
+    }
+
+    let mut xs: Vec<f32> = sets.iter()
+        .flat_map(|points| points.iter().map(|&(x, _)| x))
+        .collect();
+    xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    xs.dedup();
+
+    let mut nominator: f32 = 0.0;
+    let mut denominator: f32 = 0.0;
+    let mut cursors = vec![0; sets.len()];
+    let mut lines = Vec::with_capacity(sets.len());
+
+    for window in xs.windows(2) {
+        let (x1, x2) = (window[0], window[1]);
+        let middle = (x1 + x2) / 2.0;
+
+        lines.clear();
+        for (points, cursor) in sets.iter().zip(cursors.iter_mut()) {
+            while *cursor + 1 < points.len() && points[*cursor + 1].0 <= middle {
+                *cursor += 1;
+            }
+            let line =
+                if *cursor + 1 < points.len() && points[*cursor].0 <= middle {
+                    let (px1, py1) = points[*cursor];
+                    let (px2, py2) = points[*cursor + 1];
+                    let at = |x: f32| py1 + (x - px1) * (py2 - py1) / (px2 - px1);
+                    (at(x1), at(x2))
+                }
+                else {
+                    (0.0, 0.0)
+                };
+            lines.push(line);
+        }
+
+        envelope_integral(x1, x2, &lines, lines.len(), &mut nominator, &mut denominator);
+    }
+
+    Some(nominator / denominator)
+}
+
+fn bisector(points: &[(f32, f32)]) -> f32 {
+    let area = |x1: f32, x2: f32, y1: f32, y2: f32| (x2 - x1) * (y1 + y2) / 2.0;
+
+    let total: f32 = points.windows(2)
+        .map(|w| area(w[0].0, w[1].0, w[0].1, w[1].1))
+        .sum();
+
+    if total <= 0.0 {
+        return f32::NAN;
+    }
+
+    let half = total / 2.0;
+    let mut accumulated = 0.0;
+
+    for window in points.windows(2) {
+        let (x1, y1) = window[0];
+        let (x2, y2) = window[1];
+        let segment = area(x1, x2, y1, y2);
+
+        if accumulated + segment >= half {
+            let target = half - accumulated;
+            let slope = (y2 - y1) / (x2 - x1);
+            let d =
+                if f32::abs(slope) < MAXIMUM_EPSILON {
+                    target / y1
+                }
+                else {
+                    (-y1 + f32::sqrt(y1 * y1 + 2.0 * slope * target)) / slope
+                };
+            return x1 + d;
+        }
+        accumulated += segment;
+    }
+
+    points[points.len() - 1].0
+}
+
+fn maxima(points: &[(f32, f32)]) -> Vec<f32> {
+    let height = points.iter()
+        .fold(0.0, |height, &(_, y)| f32::max(height, y));
+
+    if height <= 0.0 {
+        return Vec::new();
+    }
+
+    points.iter()
+        .filter(|&&(_, y)| y >= height - MAXIMUM_EPSILON)
+        .map(|&(x, _)| x)
+        .collect()
+}
+
+fn set_peak(output_set: &OutputSet, output: &Output) -> f32 {
+    let f = match output_set.term.membership() {
+        Some(f) => f,
+        None => return f32::NAN,
+    };
+
+    if let Some(peak) = f.shape().and_then(|shape| shape.peak()) {
+        return peak;
+    }
+
+    let min = output.min;
+    let max = output.max;
+    let steps = output.resolution;
+
+    let points: Vec<(f32, f32)> = (0..=steps)
+        .map(|i| {
+            let x = i as f32 * (max - min) / (steps as f32) + min;
+            (x, f.eval(x))
+        })
+        .collect();
+
+    let maxima = maxima(&points);
+    maxima.iter().sum::<f32>() / maxima.len() as f32
+}
+
+fn weighted_average(activation: Activation) -> f32 {
+    let mut nominator: f32 = 0.0;
+    let mut denominator: f32 = 0.0;
+
+    for (output_set, weight) in activation.sets() {
+        nominator += weight * set_peak(output_set, activation.output);
+        denominator += weight;
     }
 
     nominator / denominator
 }
 
+fn sample_interval_output(
+    activation: Activation,
+    accumulation: SNorm)
+    -> Vec<(f32, f32, f32)>
+{
+    let output = activation.output;
+    let min = output.min;
+    let max = output.max;
+    let steps = output.resolution;
+
+    (0..=steps)
+        .map(|i| {
+            let x = i as f32 * (max - min) / (steps as f32) + min;
+            let (lower, upper) = activation.interval_sets()
+                .fold((0.0, 0.0), |(lower, upper), (output_set, lower_level, upper_level)| {
+                    let (lower_membership, upper_membership) = output_set.term.membership()
+                        .map_or((0.0, 0.0), |f| f.eval_interval(x));
+                    let implication = activation.implication;
+                    (accumulation.apply(lower, implication.apply(lower_level, lower_membership)),
+                     accumulation.apply(upper, implication.apply(upper_level, upper_membership)))
+                });
+            (x, lower, upper)
+        })
+        .collect()
+}
+
+// Karnik-Mendel iterations for the endpoints of the type-reduced set of
+// points (x, lower weight, upper weight).
+fn karnik_mendel(points: &mut [(f32, f32, f32)]) -> (f32, f32) {
+    points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    let points = &*points;
+    let endpoint = |left: bool| {
+        let average = |weight: &dyn Fn(f32, f32, f32) -> f32| {
+            let (nominator, denominator) = points.iter()
+                .fold((0.0, 0.0), |(nominator, denominator), &(x, lower, upper)| {
+                    let w = weight(x, lower, upper);
+                    (nominator + w * x, denominator + w)
+                });
+            nominator / denominator
+        };
+
+        let mut y = average(&|_, lower, upper| (lower + upper) / 2.0);
+        for _ in 0..=points.len() {
+            let next = average(&|x, lower, upper| {
+                if (x <= y) == left { upper } else { lower }
+            });
+            if next.is_nan() || f32::abs(next - y) <= MAXIMUM_EPSILON {
+                return next;
+            }
+            y = next;
+        }
+        y
+    };
+
+    (endpoint(true), endpoint(false))
+}
+
+fn type_reduce(
+    activation: Activation,
+    accumulation: SNorm,
+    sugeno_firings: &[(f32, f32, f32)])
+    -> (f32, f32)
+{
+    let output = activation.output;
+    let mut points: Vec<(f32, f32, f32)> = match output.kind {
+        OutputKind::Sugeno => sugeno_firings.to_vec(),
+        OutputKind::Mamdani if output.defuzzifier == Defuzzifier::WeightedAverage => {
+            activation.interval_sets()
+                .map(|(output_set, lower, upper)| (set_peak(output_set, output), lower, upper))
+                .collect()
+        },
+        OutputKind::Mamdani => sample_interval_output(activation, accumulation),
+    };
+    karnik_mendel(&mut points)
+}
+
+fn defuzzificate(activation: Activation, accumulation: SNorm) -> f32 {
+    use Defuzzifier::*;
+
+    let defuzzifier = activation.output.defuzzifier;
+    if defuzzifier == WeightedAverage {
+        return weighted_average(activation);
+    }
+
+    if defuzzifier == Centroid && accumulation == SNorm::Maximum {
+        if let Some(value) = exact_centroid(activation) {
+            return value;
+        }
+    }
+
+    let points = sample_output(activation, accumulation);
+
+    match defuzzifier {
+        Centroid => centroid(&points),
+        Bisector => bisector(&points),
+        MeanOfMaximum => {
+            let maxima = maxima(&points);
+            maxima.iter().sum::<f32>() / maxima.len() as f32
+        },
+        SmallestOfMaximum =>
+            maxima(&points).first().cloned().unwrap_or(f32::NAN),
+        LargestOfMaximum =>
+            maxima(&points).last().cloned().unwrap_or(f32::NAN),
+        WeightedAverage => unreachable!(),
+    }
+}
+
 impl Fuzzy {
-    pub fn evaluate(&mut self, rule_set: RuleSetId) {
-        let inputs = &self.inputs;
-        let input_sets = &mut self.input_sets;
-        let outputs = &mut self.outputs;
-        let output_sets = &mut self.output_sets;
-        let rules = &mut self.rules;
-        let rule_sets = &self.rule_sets;
+    pub fn is_interval_rule_set(&self, rule_set: RuleSetId) -> bool {
+        self.rule_sets[rule_set.id].rules.iter().any(|rule| {
+            let rule = &self.rules[rule.id];
+            let mut input_sets = Vec::new();
+            rule.antecedent.collect_input_sets(&mut input_sets);
+            input_sets.iter().any(|id| self.input_sets[id.id].f.is_interval())
+                || self.output_sets[rule.output_set.id].term.membership()
+                    .is_some_and(MembershipFunction::is_interval)
+        })
+    }
+
+    pub fn evaluate(&mut self, rule_set: RuleSetId) -> Result<Evaluation, FuzzyError> {
+        let inputs = self.input_values();
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let result = self.evaluate_with(rule_set, &inputs, &mut scratch);
+        self.scratch = scratch;
+        self.evaluation = result?;
+        Ok(self.evaluation.clone())
+    }
+
+    pub fn evaluate_with(
+        &self, rule_set: RuleSetId, inputs: &[f32], scratch: &mut Scratch)
+        -> Result<Evaluation, FuzzyError>
+    {
+        if rule_set.id >= self.rule_sets.len() {
+            return Err(FuzzyError::UnknownRuleSet(rule_set));
+        }
+        if inputs.len() != self.inputs.len() {
+            return Err(FuzzyError::InputCount {
+                expected: self.inputs.len(),
+                found: inputs.len(),
+            });
+        }
+
+        scratch.rule_set = None;
+        scratch.inputs.clear();
+        for (id, &value) in inputs.iter().enumerate() {
+            scratch.inputs.push(self.check_input(InputId { id }, value)?);
+        }
+        scratch.prepare(self);
+        scratch.rule_set = Some(rule_set);
+
+        // Outputs the rule set doesn't reach are undefined, whatever an
+        // earlier call left in the scratch.
+        for value in scratch.values.iter_mut() {
+            *value = f32::NAN;
+        }
+        for interval in scratch.intervals.iter_mut() {
+            *interval = (f32::NAN, f32::NAN);
+        }
 
         let dirty_input_sets = 
             collect_dirty_input_sets(
-                rule_sets, rules, rule_set);
+                &self.rule_sets, &self.rules, rule_set);
 
         for input_set in dirty_input_sets.iter() {
-            input_sets[input_set.id].membership = {
-                let f = &input_sets[input_set.id].f;
-                let input = input_sets[input_set.id].input;
-                let value = inputs[input.id].value;
-                f(value)
-            };
+            let set = &self.input_sets[input_set.id];
+            let (lower, upper) = set.f.eval_interval(scratch.inputs[set.input.id]);
+            scratch.input_memberships[input_set.id] = upper;
+            scratch.lower_input_memberships[input_set.id] = lower;
         }
 
-        let active_rules: Vec<RuleId> = rule_sets[rule_set.id].rules.clone();
+        let active_rules = &self.rule_sets[rule_set.id].rules;
+        let operators = self.rule_sets[rule_set.id].operators;
+        let interval = self.is_interval_rule_set(rule_set);
 
-        for output in outputs.iter_mut() {
-            output.cached_output_sets.clear();
+        for output_sets in scratch.active_output_sets.iter_mut() {
+            output_sets.clear();
+        }
+        for sums in scratch.sugeno_sums.iter_mut() {
+            *sums = (0.0, 0.0);
+        }
+        for firings in scratch.sugeno_firings.iter_mut() {
+            firings.clear();
         }
 
         for rule in active_rules.iter() {
-            let output_set = rules[rule.id].output_set;
-            output_sets[output_set.id].input_membership = None;
+            let output_set = self.rules[rule.id].output_set;
+            scratch.output_memberships[output_set.id] = None;
+            scratch.lower_output_memberships[output_set.id] = None;
         }
 
+        let accumulate = |membership: &mut Option<f32>, value: f32| {
+            *membership = match *membership {
+                None => Some(value),
+                Some(previous) => Some(operators.accumulation.apply(previous, value)),
+            };
+        };
+
         for rule in active_rules.iter() {
-            let input_membership = 
-                compute_input_membership(rules, input_sets, *rule);
+            let mut input_membership = 
+                compute_input_membership(
+                    &self.rules, &scratch.input_memberships, *rule, operators);
+            let mut lower_membership = input_membership;
 
-            let output_set = rules[rule.id].output_set;
+            if interval {
+                // Negations can swap which bound comes from which memberships.
+                let other = compute_input_membership(
+                    &self.rules, &scratch.lower_input_memberships, *rule, operators);
+                lower_membership = f32::min(input_membership, other);
+                input_membership = f32::max(input_membership, other);
+            }
 
-            output_sets[output_set.id].set_input_membership(input_membership);
+            let output_set = self.rules[rule.id].output_set;
 
-            outputs[output_sets[output_set.id].output.id]
-                .cached_output_sets.push(output_set);
-        }
+            accumulate(&mut scratch.output_memberships[output_set.id], input_membership);
+            accumulate(&mut scratch.lower_output_memberships[output_set.id], lower_membership);
 
-        for output in outputs.iter_mut() {
-            use std::cmp::Ordering;
+            let output = self.output_sets[output_set.id].output;
 
-            let ordering = |a: &OutputSetId, b: &OutputSetId| {
-                if a.id < b.id {
-                    Ordering::Less
-                }
-                else if a.id > b.id {
-                    Ordering::Greater
-                }
-                else {
-                    Ordering::Equal
+            if let Some(consequent) = self.output_sets[output_set.id].term.consequent() {
+                let value = consequent.eval(&scratch.inputs);
+                let (nominator, denominator) = &mut scratch.sugeno_sums[output.id];
+                *nominator += input_membership * value;
+                *denominator += input_membership;
+                if interval {
+                    scratch.sugeno_firings[output.id].push((value, lower_membership, input_membership));
                 }
-            };
+            }
 
-            output.cached_output_sets.sort_by(ordering);
-            output.cached_output_sets.dedup_by(|a, b| a.id == b.id);
+            scratch.active_output_sets[output.id].push(output_set);
         }
 
-        let output_results: Vec<(OutputId, f32)> =
-            outputs.iter().enumerate()
-                .filter(|(_index, output)| !output.cached_output_sets.is_empty())
-                .map(|(id, output)| (OutputId { id }, defuzzificate(output_sets, output)))
-                .collect();
+        for output_sets in scratch.active_output_sets.iter_mut() {
+            output_sets.sort_by_key(|output_set| output_set.id);
+            output_sets.dedup_by(|a, b| a.id == b.id);
+        }
 
-        for result in output_results.iter() {
-            let (output, value) = *result;
-            outputs[output.id].value = value;
+        let mut fallbacks = Vec::new();
+        for (id, output) in self.outputs.iter().enumerate() {
+            let output_id = OutputId { id };
+            if !scratch.is_active(output_id) {
+                continue;
+            }
+
+            let activation = Activation::new(self, scratch, output_id);
+            let fired = match output.kind {
+                OutputKind::Mamdani => activation.sets().any(|(_, level)| level > 0.0),
+                OutputKind::Sugeno => scratch.sugeno_sums[id].1 > 0.0,
+            };
+
+            let (lower, upper) =
+                if fired && interval {
+                    type_reduce(activation, operators.accumulation, &scratch.sugeno_firings[id])
+                }
+                else if fired {
+                    let value = match output.kind {
+                        OutputKind::Mamdani =>
+                            defuzzificate(activation, operators.accumulation),
+                        OutputKind::Sugeno => {
+                            let (nominator, denominator) = scratch.sugeno_sums[id];
+                            nominator / denominator
+                        },
+                    };
+                    (value, value)
+                }
+                else {
+                    fallbacks.push(output_id);
+                    let value = match output.fallback {
+                        Fallback::Default(value) => value,
+                        Fallback::HoldPrevious => scratch.held[id],
+                        Fallback::Undefined => f32::NAN,
+                    };
+                    (value, value)
+                };
+            scratch.values[id] = (lower + upper) / 2.0;
+            scratch.intervals[id] = (lower, upper);
+            scratch.held[id] = scratch.values[id];
         }
+
+        Ok(Evaluation {
+            values: scratch.values.clone(),
+            intervals: scratch.intervals.clone(),
+            fallbacks,
+        })
     }
 }
 
@@ -211,39 +817,452 @@ mod tests {
         let r2 = fuzzy.add_rule(&[is3, is4], os2);
         let rs1 = fuzzy.add_rule_set(&[r1, r2]);
 
-        fuzzy.set_input(i1, 1.0);
-        fuzzy.set_input(i2, 2.0);
+        fuzzy.set_input(i1, 1.0).unwrap();
+        fuzzy.set_input(i2, 2.0).unwrap();
 
-        fuzzy.evaluate(rs1);
+        fuzzy.evaluate(rs1).unwrap();
 
-        assert_relative_eq!(fuzzy.input_sets[is1.id].membership, 0.25);
-        assert_relative_eq!(fuzzy.input_sets[is2.id].membership, 1.0);
-        assert_relative_eq!(fuzzy.input_sets[is3.id].membership, 0.75);
-        assert_relative_eq!(fuzzy.input_sets[is4.id].membership, 0.0);
+        assert_relative_eq!(fuzzy.scratch.input_memberships[is1.id], 0.25);
+        assert_relative_eq!(fuzzy.scratch.input_memberships[is2.id], 1.0);
+        assert_relative_eq!(fuzzy.scratch.input_memberships[is3.id], 0.75);
+        assert_relative_eq!(fuzzy.scratch.input_memberships[is4.id], 0.0);
 
         assert_relative_eq!(
-            fuzzy.output_sets[os1.id].input_membership.unwrap(), 0.25);
+            fuzzy.scratch.output_memberships[os1.id].unwrap(), 0.25);
         assert_relative_eq!(
-            fuzzy.output_sets[os2.id].input_membership.unwrap(), 0.0);
+            fuzzy.scratch.output_memberships[os2.id].unwrap(), 0.0);
     
         assert_relative_eq!(
             output_fuzzy_function(
-                &fuzzy.output_sets, &fuzzy.outputs[o1.id], 0.25),
+                Activation::new(&fuzzy, &fuzzy.scratch, o1), 0.25,
+                SNorm::Maximum),
             0.25
         );
         assert_relative_eq!(
             output_fuzzy_function(
-                &fuzzy.output_sets, &fuzzy.outputs[o1.id], 0.5),
+                Activation::new(&fuzzy, &fuzzy.scratch, o1), 0.5,
+                SNorm::Maximum),
             0.25
         );
         assert_relative_eq!(
             output_fuzzy_function(
-                &fuzzy.output_sets, &fuzzy.outputs[o1.id], 0.125),
+                Activation::new(&fuzzy, &fuzzy.scratch, o1), 0.125,
+                SNorm::Maximum),
             0.125
         );
         
-        assert_relative_eq!(fuzzy.outputs[o1.id].value, 0.5595238);
-        assert_relative_eq!(fuzzy.outputs[o2.id].value, 0.0);
+        assert_relative_eq!(fuzzy.scratch.values[o1.id], 0.5595238);
+        assert!(fuzzy.scratch.values[o2.id].is_nan());
+    }
+
+    #[test]
+    fn test_rule_set_operators() {
+        let mut fuzzy = Fuzzy::new();
+        let i1 = fuzzy.add_input(0.0, 1.0);
+        let o1 = fuzzy.add_output(0.0, 1.0);
+
+        let is1 = fuzzy.add_input_set(
+            i1, Shape::RightShoulder { a: 0.0, b: 1.0 });
+        let is2 = fuzzy.add_input_set(
+            i1, Shape::LeftShoulder { a: 0.0, b: 1.0 });
+
+        let os1 = fuzzy.add_output_set(
+            o1, Shape::Triangle { a: 0.0, b: 0.5, c: 1.0 });
+
+        let r1 = fuzzy.add_rule(&[is1, is2], os1);
+        let r2 = fuzzy.add_rule(&[is1], os1);
+
+        let min_max = fuzzy.add_rule_set(&[r1, r2]);
+        let product_sum = fuzzy.add_rule_set_with_operators(
+            &[r1, r2],
+            Operators {
+                and: TNorm::Product,
+                accumulation: SNorm::ProbabilisticSum,
+                ..Operators::default()
+            });
+
+        fuzzy.set_input(i1, 0.4).unwrap();
+
+        fuzzy.evaluate(min_max).unwrap();
+        assert_relative_eq!(
+            fuzzy.scratch.output_memberships[os1.id].unwrap(), 0.4);
+
+        fuzzy.evaluate(product_sum).unwrap();
+        assert_relative_eq!(
+            fuzzy.scratch.output_memberships[os1.id].unwrap(), 0.544);
+    }
+
+    fn two_set_fuzzy() -> (Fuzzy, InputId, OutputId, RuleSetId) {
+        let mut fuzzy = Fuzzy::new();
+        let i1 = fuzzy.add_input(0.0, 1.0);
+        let o1 = fuzzy.add_output(0.0, 1.0);
+
+        let is1 = fuzzy.add_input_set(
+            i1, Shape::LeftShoulder { a: 0.0, b: 1.0 });
+        let is2 = fuzzy.add_input_set(
+            i1, Shape::RightShoulder { a: 0.0, b: 1.0 });
+
+        let os1 = fuzzy.add_output_set(
+            o1, Shape::Triangle { a: 0.0, b: 0.25, c: 0.5 });
+        let os2 = fuzzy.add_output_set(
+            o1, Shape::Triangle { a: 0.5, b: 0.75, c: 1.0 });
+
+        let r1 = fuzzy.add_rule(&[is1], os1);
+        let r2 = fuzzy.add_rule(&[is2], os2);
+        let rs1 = fuzzy.add_rule_set(&[r1, r2]);
+
+        (fuzzy, i1, o1, rs1)
+    }
+
+    #[test]
+    fn test_defuzzifiers() {
+        let (mut fuzzy, i1, o1, rs1) = two_set_fuzzy();
+        fuzzy.set_input(i1, 0.25).unwrap();
+
+        let mut evaluate = |defuzzifier| {
+            fuzzy.set_defuzzifier(o1, defuzzifier);
+            fuzzy.evaluate(rs1).unwrap();
+            fuzzy.get_output(o1).unwrap()
+        };
+
+        assert_relative_eq!(
+            evaluate(Defuzzifier::WeightedAverage), 0.375);
+        assert_relative_eq!(
+            evaluate(Defuzzifier::SmallestOfMaximum), 0.2, epsilon = 1e-5);
+        assert_relative_eq!(
+            evaluate(Defuzzifier::LargestOfMaximum), 0.3, epsilon = 1e-5);
+        assert_relative_eq!(
+            evaluate(Defuzzifier::MeanOfMaximum), 0.25, epsilon = 1e-5);
+
+        let centroid = evaluate(Defuzzifier::Centroid);
+        let bisector = evaluate(Defuzzifier::Bisector);
+        assert!(centroid > 0.25 && centroid < 0.5);
+        assert!(bisector > 0.25 && bisector < centroid);
+    }
+
+    #[test]
+    fn test_bisector_and_resolution() {
+        let (mut fuzzy, i1, o1, rs1) = two_set_fuzzy();
+        fuzzy.set_input(i1, 0.0).unwrap();
+
+        fuzzy.set_defuzzifier(o1, Defuzzifier::Bisector);
+        fuzzy.evaluate(rs1).unwrap();
+        assert_relative_eq!(fuzzy.get_output(o1).unwrap(), 0.25, epsilon = 1e-5);
+
+        fuzzy.set_input(i1, 0.25).unwrap();
+        fuzzy.set_defuzzifier(o1, Defuzzifier::MeanOfMaximum);
+        fuzzy.set_resolution(o1, 4);
+        fuzzy.evaluate(rs1).unwrap();
+        assert_relative_eq!(fuzzy.get_output(o1).unwrap(), 0.25, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_exact_centroid() {
+        let (mut fuzzy, i1, o1, rs1) = two_set_fuzzy();
+        fuzzy.set_input(i1, 0.25).unwrap();
+        fuzzy.evaluate(rs1).unwrap();
+        assert_relative_eq!(fuzzy.get_output(o1).unwrap(), 0.4090909, epsilon = 1e-6);
+
+        let mut fuzzy = Fuzzy::new();
+        let i1 = fuzzy.add_input(0.0, 1.0);
+        let o1 = fuzzy.add_output(0.0, 1.0);
+        let is1 = fuzzy.add_input_set(i1, Shape::LeftShoulder { a: 0.2, b: 0.8 });
+        let is2 = fuzzy.add_input_set(i1, Shape::Triangle { a: 0.0, b: 0.5, c: 1.0 });
+        let is3 = fuzzy.add_input_set(i1, Shape::RightShoulder { a: 0.2, b: 0.8 });
+        let os1 = fuzzy.add_output_set(
+            o1, Shape::PiecewiseLinear(vec![(0.3, 1.0), (0.7, 1.0), (0.7, 0.0)]));
+        let os2 = fuzzy.add_output_set(o1, Shape::Trapezoid { a: 0.1, b: 0.4, c: 0.6, d: 0.9 });
+        let os3 = fuzzy.add_output_set(o1, Shape::RightShoulder { a: 0.5, b: 0.95 });
+        let r1 = fuzzy.add_rule(&[is1], os1);
+        let r2 = fuzzy.add_rule(&[is2], os2);
+        let r3 = fuzzy.add_rule(&[is3], os3);
+        let rs1 = fuzzy.add_rule_set(&[r1, r2, r3]);
+
+        for &x in [0.1, 0.35, 0.5, 0.65, 0.9].iter() {
+            fuzzy.set_input(i1, x).unwrap();
+            fuzzy.evaluate(rs1).unwrap();
+
+            fuzzy.set_resolution(o1, 20000);
+            let points = sample_output(Activation::new(&fuzzy, &fuzzy.scratch, o1), SNorm::Maximum);
+            assert_relative_eq!(fuzzy.get_output(o1).unwrap(), centroid(&points), epsilon = 1e-4);
+        }
+
+        let mut fuzzy = Fuzzy::new();
+        let i1 = fuzzy.add_input(0.0, 1.0);
+        let o1 = fuzzy.add_output(0.0, 1.0);
+        let is1 = fuzzy.add_input_set(i1, Shape::Triangle { a: 0.0, b: 0.5, c: 1.0 });
+        let os1 = fuzzy.add_output_set(o1, Shape::Triangle { a: -0.5, b: 0.25, c: 0.5 });
+        let os2 = fuzzy.add_output_set(o1, Shape::Triangle { a: 0.5, b: 1.0, c: 1.5 });
+        let os3 = fuzzy.add_output_set(o1, Shape::PiecewiseLinear(vec![]));
+        let r1 = fuzzy.add_rule(&[is1], os1);
+        let r2 = fuzzy.add_rule(&[is1], os2);
+        let r3 = fuzzy.add_rule(&[is1], os3);
+        let rs1 = fuzzy.add_rule_set(&[r1, r2, r3]);
+
+        for &x in [0.3, 0.5].iter() {
+            fuzzy.set_input(i1, x).unwrap();
+            fuzzy.evaluate(rs1).unwrap();
+
+            fuzzy.set_resolution(o1, 20000);
+            let points = sample_output(Activation::new(&fuzzy, &fuzzy.scratch, o1), SNorm::Maximum);
+            assert_relative_eq!(fuzzy.get_output(o1).unwrap(), centroid(&points), epsilon = 1e-4);
+        }
+        assert_relative_eq!(fuzzy.get_output(o1).unwrap(), 0.4761905, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_sugeno() {
+        let mut fuzzy = Fuzzy::new();
+        let i1 = fuzzy.add_input(0.0, 1.0);
+        let i2 = fuzzy.add_input(0.0, 10.0);
+        let o1 = fuzzy.add_sugeno_output(0.0, 1.0);
+        let o2 = fuzzy.add_sugeno_output(0.0, 20.0);
+
+        let is1 = fuzzy.add_input_set(
+            i1, Shape::LeftShoulder { a: 0.0, b: 1.0 });
+        let is2 = fuzzy.add_input_set(
+            i1, Shape::RightShoulder { a: 0.0, b: 1.0 });
+
+        let low = fuzzy.add_consequent(o1, Consequent::Constant(0.2));
+        let high = fuzzy.add_consequent(o1, Consequent::Constant(0.8));
+        let linear = fuzzy.add_consequent(
+            o2,
+            Consequent::Linear {
+                coefficients: vec![(i1, 2.0), (i2, 1.0)],
+                constant: 1.0,
+            });
+        let zero = fuzzy.add_consequent(o2, Consequent::Constant(0.0));
+
+        let r1 = fuzzy.add_rule(&[is1], low);
+        let r2 = fuzzy.add_rule(&[is2], high);
+        let r3 = fuzzy.add_rule(&[is2], high);
+        let r4 = fuzzy.add_rule(&[is1], linear);
+        let r5 = fuzzy.add_rule(&[is2], zero);
+
+        let rs1 = fuzzy.add_rule_set(&[r1, r2, r3, r4, r5]);
+
+        fuzzy.set_input(i1, 0.25).unwrap();
+        fuzzy.set_input(i2, 4.0).unwrap();
+        fuzzy.evaluate(rs1).unwrap();
+
+        assert_relative_eq!(
+            fuzzy.get_output(o1).unwrap(),
+            (0.75 * 0.2 + 0.25 * 0.8 + 0.25 * 0.8) / 1.25);
+        assert_relative_eq!(fuzzy.get_output(o2).unwrap(), 0.75 * 5.5);
+    }
+
+    #[test]
+    fn test_fallbacks() {
+        let (mut fuzzy, i1, o1, rs1) = two_set_fuzzy();
+        let r1 = fuzzy.rule_sets[rs1.id].rules[0];
+        let rs2 = fuzzy.add_rule_set(&[r1]);
+
+        fuzzy.set_input(i1, 0.25).unwrap();
+        assert!(fuzzy.evaluate(rs2).unwrap().fallbacks.is_empty());
+        let previous = fuzzy.get_output(o1).unwrap();
+
+        fuzzy.set_input(i1, 1.0).unwrap();
+        let evaluation = fuzzy.evaluate(rs2).unwrap();
+        assert!(evaluation.fell_back(o1));
+        assert_eq!(
+            fuzzy.get_output(o1),
+            Err(FuzzyError::Undefined { output: "output0".to_string() }));
+
+        fuzzy.set_fallback(o1, Fallback::Default(0.5));
+        fuzzy.evaluate(rs2).unwrap();
+        assert_relative_eq!(fuzzy.get_output(o1).unwrap(), 0.5);
+
+        fuzzy.set_input(i1, 0.25).unwrap();
+        fuzzy.evaluate(rs2).unwrap();
+        fuzzy.set_fallback(o1, Fallback::HoldPrevious);
+        fuzzy.set_input(i1, 1.0).unwrap();
+        assert!(fuzzy.evaluate(rs2).unwrap().fell_back(o1));
+        assert_relative_eq!(fuzzy.get_output(o1).unwrap(), previous);
+    }
+
+    #[test]
+    fn test_evaluate_with() {
+        let (mut fuzzy, i1, o1, rs1) = two_set_fuzzy();
+        let expected: Vec<f32> = (0..=10)
+            .map(|i| {
+                fuzzy.set_input(i1, i as f32 / 10.0).unwrap();
+                fuzzy.evaluate(rs1).unwrap();
+                fuzzy.get_output(o1).unwrap()
+            })
+            .collect();
+
+        let fuzzy = &fuzzy;
+        let results: Vec<Vec<f32>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| scope.spawn(move || {
+                    let mut scratch = Scratch::default();
+                    (0..=10)
+                        .map(|i| {
+                            let evaluation = fuzzy
+                                .evaluate_with(rs1, &[i as f32 / 10.0], &mut scratch)
+                                .unwrap();
+                            evaluation.value(o1).unwrap()
+                        })
+                        .collect()
+                }))
+                .collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+        for values in results.iter() {
+            assert_eq!(values, &expected);
+        }
+
+        let mut other = Fuzzy::new();
+        let x = other.add_input(0.0, 1.0);
+        let y1 = other.add_output(0.0, 1.0);
+        let y2 = other.add_output(0.0, 1.0);
+        let low = other.add_input_set(x, Shape::LeftShoulder { a: 0.0, b: 1.0 });
+        let set1 = other.add_output_set(y1, Shape::Triangle { a: 0.0, b: 0.5, c: 1.0 });
+        let set2 = other.add_output_set(y2, Shape::Triangle { a: 0.0, b: 0.5, c: 1.0 });
+        let rule1 = other.add_rule(&[low], set1);
+        let rule2 = other.add_rule(&[low], set2);
+        let first = other.add_rule_set(&[rule1]);
+        let second = other.add_rule_set(&[rule2]);
+        let mut scratch = Scratch::default();
+        assert!(other.evaluate_with(first, &[0.5], &mut scratch).unwrap().value(y1).is_some());
+        let evaluation = other.evaluate_with(second, &[0.5], &mut scratch).unwrap();
+        assert_eq!(evaluation.value(y1), None);
+        assert!(evaluation.value(y2).is_some());
+
+        let mut scratch = Scratch::default();
+        assert_eq!(
+            fuzzy.evaluate_with(rs1, &[], &mut scratch),
+            Err(FuzzyError::InputCount { expected: 1, found: 0 }));
+        assert_eq!(
+            fuzzy.evaluate_with(RuleSetId { id: 5 }, &[0.5], &mut scratch),
+            Err(FuzzyError::UnknownRuleSet(RuleSetId { id: 5 })));
+        assert!(fuzzy.trace_with(&scratch).is_none());
+    }
+
+    #[test]
+    fn test_rule_weights() {
+        let (mut fuzzy, i1, o1, rs1) = two_set_fuzzy();
+        fuzzy.set_defuzzifier(o1, Defuzzifier::WeightedAverage);
+        fuzzy.set_input(i1, 0.5).unwrap();
+
+        fuzzy.evaluate(rs1).unwrap();
+        assert_relative_eq!(fuzzy.get_output(o1).unwrap(), 0.5);
+
+        let r2 = fuzzy.rule_sets[rs1.id].rules[1];
+        assert_relative_eq!(fuzzy.rule_weight(r2), 1.0);
+
+        fuzzy.set_rule_weight(r2, 0.2);
+        fuzzy.evaluate(rs1).unwrap();
+        assert_relative_eq!(
+            fuzzy.get_output(o1).unwrap(), (0.5 * 0.25 + 0.1 * 0.75) / 0.6);
+    }
+
+    #[test]
+    fn test_compound_rules() {
+        let mut fuzzy = Fuzzy::new();
+        let i1 = fuzzy.add_input(0.0, 1.0);
+        let i2 = fuzzy.add_input(0.0, 1.0);
+        let o1 = fuzzy.add_sugeno_output(0.0, 1.0);
+
+        let is1 = fuzzy.add_input_set(
+            i1, Shape::RightShoulder { a: 0.0, b: 1.0 });
+        let is2 = fuzzy.add_input_set(
+            i2, Shape::RightShoulder { a: 0.0, b: 1.0 });
+
+        let os1 = fuzzy.add_consequent(o1, Consequent::Constant(1.0));
+
+        let r1 = fuzzy.add_compound_rule(
+            Antecedent::is(is1).or(Antecedent::is(is2)), os1);
+        let r2 = fuzzy.add_compound_rule(
+            Antecedent::is(is1).and(!Antecedent::is(is2)), os1);
+        let r3 = fuzzy.add_compound_rule(
+            Antecedent::is(is1).very().and(Antecedent::is(is2).somewhat()),
+            os1);
+
+        let rs1 = fuzzy.add_rule_set(&[r1]);
+        let rs2 = fuzzy.add_rule_set(&[r2]);
+        let rs3 = fuzzy.add_rule_set(&[r3]);
+        let rs4 = fuzzy.add_rule_set_with_operators(
+            &[r1],
+            Operators {
+                or: SNorm::ProbabilisticSum,
+                ..Operators::default()
+            });
+
+        fuzzy.set_input(i1, 0.5).unwrap();
+        fuzzy.set_input(i2, 0.16).unwrap();
+
+        let mut strength = |rule_set| {
+            fuzzy.evaluate(rule_set).unwrap();
+            fuzzy.scratch.output_memberships[os1.id].unwrap()
+        };
+
+        assert_relative_eq!(strength(rs1), 0.5);
+        assert_relative_eq!(strength(rs2), 0.5);
+        assert_relative_eq!(strength(rs3), 0.25);
+        assert_relative_eq!(strength(rs4), 0.58);
+    }
+
+    #[test]
+    fn test_karnik_mendel() {
+        let (left, right) = karnik_mendel(&mut [(1.0, 0.5, 1.0), (0.0, 0.5, 1.0)]);
+        assert_relative_eq!(left, 1.0 / 3.0);
+        assert_relative_eq!(right, 2.0 / 3.0);
+
+        let (left, right) = karnik_mendel(&mut [(0.0, 0.2, 0.2), (2.0, 0.6, 0.6)]);
+        assert_relative_eq!(left, 1.5);
+        assert_relative_eq!(right, 1.5);
+    }
+
+    #[test]
+    fn test_interval_sets() {
+        let mut fuzzy = Fuzzy::new();
+        let i1 = fuzzy.add_input(0.0, 1.0);
+        let o1 = fuzzy.add_sugeno_output(0.0, 1.0);
+        let o2 = fuzzy.add_output(0.0, 1.0);
+
+        let low = fuzzy.add_input_set(i1, MembershipFunction::interval_around(
+            Shape::LeftShoulder { a: 0.0, b: 1.0 }, 0.25));
+        let high = fuzzy.add_input_set(i1, MembershipFunction::interval_around(
+            Shape::RightShoulder { a: 0.0, b: 1.0 }, 0.25));
+        let crisp = fuzzy.add_input_set(i1, Shape::RightShoulder { a: 0.0, b: 1.0 });
+
+        let zero = fuzzy.add_consequent(o1, Consequent::Constant(0.0));
+        let one = fuzzy.add_consequent(o1, Consequent::Constant(1.0));
+        let small = fuzzy.add_output_set(o2, Shape::Triangle { a: 0.0, b: 0.25, c: 0.5 });
+        let large = fuzzy.add_output_set(o2, Shape::Triangle { a: 0.5, b: 0.75, c: 1.0 });
+
+        let r1 = fuzzy.add_rule(&[low], zero);
+        let r2 = fuzzy.add_rule(&[high], one);
+        let r3 = fuzzy.add_rule(&[low], small);
+        let r4 = fuzzy.add_rule(&[high], large);
+        let r5 = fuzzy.add_rule(&[crisp], one);
+        let rs1 = fuzzy.add_rule_set(&[r1, r2, r3, r4]);
+        let rs2 = fuzzy.add_rule_set(&[r5]);
+
+        assert!(fuzzy.is_interval_rule_set(rs1));
+        assert!(!fuzzy.is_interval_rule_set(rs2));
+
+        fuzzy.set_input(i1, 0.5).unwrap();
+        let evaluation = fuzzy.evaluate(rs1).unwrap();
+        assert_relative_eq!(fuzzy.scratch.input_memberships[low.id], 0.6);
+        assert_relative_eq!(fuzzy.scratch.lower_input_memberships[low.id], 1.0 / 3.0);
+
+        let (left, right) = evaluation.interval(o1).unwrap();
+        assert_relative_eq!(left, (1.0 / 3.0) / (0.6 + 1.0 / 3.0));
+        assert_relative_eq!(right, 0.6 / (0.6 + 1.0 / 3.0));
+        assert_relative_eq!(evaluation.value(o1).unwrap(), 0.5);
+
+        let (left, right) = evaluation.interval(o2).unwrap();
+        assert!(left < 0.5 && right > 0.5);
+        assert_relative_eq!(evaluation.value(o2).unwrap(), 0.5, epsilon = 1e-5);
+
+        fuzzy.set_input(i1, 0.8).unwrap();
+        let evaluation = fuzzy.evaluate(rs1).unwrap();
+        assert!(evaluation.value(o1).unwrap() > 0.5);
+
+        let evaluation = fuzzy.evaluate(rs2).unwrap();
+        assert_eq!(evaluation.interval(o1), Some((1.0, 1.0)));
     }
 
     #[test]
@@ -251,4 +1270,41 @@ mod tests {
         let s = (2.0 + 3.0) / 2.0;
         assert_relative_eq!(integral(0.0, 1.0, 2.0, 3.0) / s, 0.53333336);
     }
+
+    #[test]
+    fn test_implication() {
+        let mut fuzzy = Fuzzy::new();
+        let x = fuzzy.add_input(0.0, 1.0);
+        let y = fuzzy.add_output(0.0, 1.0);
+        fuzzy.set_resolution(y, 400);
+        let half = fuzzy.add_input_set(x, Shape::Triangle { a: 0.0, b: 1.0, c: 2.0 });
+        let rising = fuzzy.add_output_set(y, Shape::RightShoulder { a: 0.0, b: 1.0 });
+        let rule = fuzzy.add_rule(&[half], rising);
+
+        let clipping = fuzzy.add_rule_set(&[rule]);
+        let scaling = fuzzy.add_rule_set_with_operators(&[rule], Operators {
+            implication: Implication::Product,
+            ..Operators::default()
+        });
+        let sampled = fuzzy.add_rule_set_with_operators(&[rule], Operators {
+            implication: Implication::Product,
+            accumulation: SNorm::ProbabilisticSum,
+            ..Operators::default()
+        });
+        fuzzy.set_input(x, 0.5).unwrap();
+
+        // Clipping y = x at 0.5 versus scaling it to y = x / 2.
+        let value = |fuzzy: &mut Fuzzy, rule_set| fuzzy.evaluate(rule_set).unwrap().value(y).unwrap();
+        assert_relative_eq!(value(&mut fuzzy, clipping), 0.22916667 / 0.375, epsilon = 1e-4);
+        assert_relative_eq!(value(&mut fuzzy, scaling), 2.0 / 3.0, epsilon = 1e-4);
+        assert_relative_eq!(value(&mut fuzzy, sampled), 2.0 / 3.0, epsilon = 1e-3);
+
+        fuzzy.set_defuzzifier(y, Defuzzifier::Bisector);
+        assert_relative_eq!(value(&mut fuzzy, scaling), f32::sqrt(0.5), epsilon = 1e-3);
+        assert_relative_eq!(value(&mut fuzzy, clipping), 0.625, epsilon = 1e-3);
+
+        let mut loaded = Fuzzy::from_fcl(&fuzzy.to_fcl("implication")).unwrap();
+        loaded.set_input(x, 0.5).unwrap();
+        assert_relative_eq!(value(&mut loaded, scaling), f32::sqrt(0.5), epsilon = 1e-3);
+    }
 }
@@ -0,0 +1,145 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TNorm {
+    Minimum,
+    Product,
+    Lukasiewicz,
+    Einstein,
+    Drastic,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SNorm {
+    Maximum,
+    ProbabilisticSum,
+    BoundedSum,
+    Einstein,
+    Drastic,
+}
+
+// How a rule's firing strength shapes its output set: Minimum clips the
+// set at that level, Product (Larsen) scales it and so keeps its shape.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Implication {
+    Minimum,
+    Product,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Operators {
+    pub and: TNorm,
+    pub or: SNorm,
+    pub accumulation: SNorm,
+    pub implication: Implication,
+}
+
+impl TNorm {
+    pub fn apply(self, a: f32, b: f32) -> f32 {
+        use TNorm::*;
+
+        match self {
+            Minimum => f32::min(a, b),
+            Product => a * b,
+            Lukasiewicz => f32::max(0.0, a + b - 1.0),
+            Einstein => a * b / (2.0 - (a + b - a * b)),
+            Drastic => {
+                if a == 1.0 {
+                    b
+                }
+                else if b == 1.0 {
+                    a
+                }
+                else {
+                    0.0
+                }
+            },
+        }
+    }
+}
+
+impl SNorm {
+    pub fn apply(self, a: f32, b: f32) -> f32 {
+        use SNorm::*;
+
+        match self {
+            Maximum => f32::max(a, b),
+            ProbabilisticSum => a + b - a * b,
+            BoundedSum => f32::min(1.0, a + b),
+            Einstein => (a + b) / (1.0 + a * b),
+            Drastic => {
+                if a == 0.0 {
+                    b
+                }
+                else if b == 0.0 {
+                    a
+                }
+                else {
+                    1.0
+                }
+            },
+        }
+    }
+}
+
+impl Implication {
+    pub fn apply(self, level: f32, membership: f32) -> f32 {
+        match self {
+            Implication::Minimum => f32::min(level, membership),
+            Implication::Product => level * membership,
+        }
+    }
+}
+
+impl Default for Operators {
+    fn default() -> Self {
+        Self {
+            and: TNorm::Minimum,
+            or: SNorm::Maximum,
+            accumulation: SNorm::Maximum,
+            implication: Implication::Minimum,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_t_norms() {
+        assert_relative_eq!(TNorm::Minimum.apply(0.4, 0.5), 0.4);
+        assert_relative_eq!(TNorm::Product.apply(0.4, 0.5), 0.2);
+        assert_relative_eq!(TNorm::Lukasiewicz.apply(0.4, 0.5), 0.0);
+        assert_relative_eq!(TNorm::Lukasiewicz.apply(0.8, 0.5), 0.3);
+        assert_relative_eq!(TNorm::Einstein.apply(0.5, 0.5), 0.2);
+        assert_relative_eq!(TNorm::Drastic.apply(1.0, 0.5), 0.5);
+        assert_relative_eq!(TNorm::Drastic.apply(0.9, 0.5), 0.0);
+
+        for norm in [TNorm::Minimum, TNorm::Product, TNorm::Lukasiewicz,
+                     TNorm::Einstein, TNorm::Drastic].iter() {
+            assert_relative_eq!(norm.apply(1.0, 0.3), 0.3);
+        }
+    }
+
+    #[test]
+    fn test_s_norms() {
+        assert_relative_eq!(SNorm::Maximum.apply(0.4, 0.5), 0.5);
+        assert_relative_eq!(SNorm::ProbabilisticSum.apply(0.4, 0.5), 0.7);
+        assert_relative_eq!(SNorm::BoundedSum.apply(0.4, 0.5), 0.9);
+        assert_relative_eq!(SNorm::BoundedSum.apply(0.8, 0.5), 1.0);
+        assert_relative_eq!(SNorm::Einstein.apply(0.5, 0.5), 0.8);
+        assert_relative_eq!(SNorm::Drastic.apply(0.0, 0.5), 0.5);
+        assert_relative_eq!(SNorm::Drastic.apply(0.1, 0.5), 1.0);
+
+        for norm in [SNorm::Maximum, SNorm::ProbabilisticSum, SNorm::BoundedSum,
+                     SNorm::Einstein, SNorm::Drastic].iter() {
+            assert_relative_eq!(norm.apply(0.0, 0.3), 0.3);
+        }
+    }
+}
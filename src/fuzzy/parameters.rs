@@ -0,0 +1,178 @@
+use std::fmt::Write;
+
+use super::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Parameter {
+    InputSet(InputSetId, usize),
+    OutputSet(OutputSetId, usize),
+    RuleWeight(RuleId),
+}
+
+fn is_linear(shape: &Shape) -> bool {
+    shape.linear_points().is_some()
+}
+
+impl Fuzzy {
+    fn output_set_function_mut(&mut self, id: OutputSetId) -> Option<&mut MembershipFunction> {
+        match self.output_sets[id.id].term {
+            OutputTerm::Membership(ref mut f) => Some(f),
+            OutputTerm::Consequent(_) => None,
+        }
+    }
+
+    // Membership parameters of every plain shape and the weights of the
+    // rules used by some rule set, in a stable order.
+    pub fn parameters(&self) -> Vec<Parameter> {
+        let mut parameters = Vec::new();
+        for (id, set) in self.input_sets.iter().enumerate() {
+            if let Some(shape) = set.f.tunable_shape() {
+                for k in 0..shape.parameters().len() {
+                    parameters.push(Parameter::InputSet(InputSetId { id }, k));
+                }
+            }
+        }
+        for (id, set) in self.output_sets.iter().enumerate() {
+            if let Some(shape) = set.term.membership().and_then(MembershipFunction::tunable_shape) {
+                for k in 0..shape.parameters().len() {
+                    parameters.push(Parameter::OutputSet(OutputSetId { id }, k));
+                }
+            }
+        }
+        for rule in self.used_rules() {
+            parameters.push(Parameter::RuleWeight(rule));
+        }
+        parameters
+    }
+
+    fn parameter_shape(&self, parameter: Parameter) -> Option<&Shape> {
+        match parameter {
+            Parameter::InputSet(id, _) => self.input_sets[id.id].f.tunable_shape(),
+            Parameter::OutputSet(id, _) =>
+                self.output_sets[id.id].term.membership().and_then(MembershipFunction::tunable_shape),
+            Parameter::RuleWeight(_) => None,
+        }
+    }
+
+    pub fn parameter(&self, parameter: Parameter) -> f32 {
+        match parameter {
+            Parameter::InputSet(_, k) | Parameter::OutputSet(_, k) =>
+                self.parameter_shape(parameter).unwrap().parameters()[k],
+            Parameter::RuleWeight(rule) => self.rule_weight(rule),
+        }
+    }
+
+    // The range of the variable a membership parameter lives on, or the
+    // unit interval for rule weights.
+    pub fn parameter_range(&self, parameter: Parameter) -> (f32, f32) {
+        match parameter {
+            Parameter::InputSet(id, _) => self.sample_range(self.input_sets[id.id].input),
+            Parameter::OutputSet(id, _) => {
+                let output = &self.outputs[self.output_sets[id.id].output.id];
+                (output.min, output.max)
+            },
+            Parameter::RuleWeight(_) => (0.0, 1.0),
+        }
+    }
+
+    pub fn parameter_name(&self, parameter: Parameter) -> String {
+        match parameter {
+            Parameter::InputSet(id, k) => format!("{}[{}]", self.input_set_name(id), k),
+            Parameter::OutputSet(id, k) => format!("{}[{}]", self.output_set_name(id), k),
+            Parameter::RuleWeight(rule) => format!("rule{}.weight", rule.id),
+        }
+    }
+
+    // Parameters are assigned together so that shapes are only repaired
+    // once all of their parameters moved. Breakpoints of linear shapes and
+    // rule weights are kept within their range.
+    pub fn set_parameters(&mut self, parameters: &[Parameter], values: &[f32]) {
+        debug_assert_eq!(parameters.len(), values.len());
+
+        let mut touched = Vec::new();
+        for (&parameter, &value) in parameters.iter().zip(values.iter()) {
+            let (min, max) = self.parameter_range(parameter);
+            let f = match parameter {
+                Parameter::InputSet(id, _) => Some(&mut self.input_sets[id.id].f),
+                Parameter::OutputSet(id, _) => self.output_set_function_mut(id),
+                Parameter::RuleWeight(rule) => {
+                    self.set_rule_weight(rule, value.clamp(min, max));
+                    continue;
+                },
+            };
+            if let Some(shape) = f.and_then(MembershipFunction::tunable_shape_mut) {
+                let k = match parameter {
+                    Parameter::InputSet(_, k) | Parameter::OutputSet(_, k) => k,
+                    Parameter::RuleWeight(_) => unreachable!(),
+                };
+                let mut params = shape.parameters();
+                params[k] = if is_linear(shape) { value.clamp(min, max) } else { value };
+                shape.set_parameters(&params);
+                touched.push(parameter);
+            }
+        }
+
+        for parameter in touched {
+            let f = match parameter {
+                Parameter::InputSet(id, _) => Some(&mut self.input_sets[id.id].f),
+                Parameter::OutputSet(id, _) => self.output_set_function_mut(id),
+                Parameter::RuleWeight(_) => None,
+            };
+            if let Some(shape) = f.and_then(MembershipFunction::tunable_shape_mut) {
+                shape.repair();
+            }
+        }
+    }
+
+    pub fn format_parameters(&self) -> String {
+        let mut text = String::new();
+        for parameter in self.parameters() {
+            writeln!(text, "{} = {}",
+                self.parameter_name(parameter),
+                self.parameter(parameter)).unwrap();
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameters() {
+        let mut fuzzy = Fuzzy::new();
+        let x = fuzzy.add_input(0.0, 10.0);
+        let y = fuzzy.add_output(0.0, 1.0);
+        let near = fuzzy.add_input_set(x, Shape::LeftShoulder { a: 2.0, b: 4.0 });
+        fuzzy.add_input_set(x, Box::new(|x: f32| x / 10.0));
+        let slow = fuzzy.add_output_set(y, Shape::Gaussian { mean: 0.2, sigma: 0.1 });
+        let rule = fuzzy.add_weighted_compound_rule(Antecedent::all(&[near]), slow, 0.5);
+        fuzzy.add_rule_set(&[rule]);
+        fuzzy.set_input_name(x, "x");
+        fuzzy.set_input_set_name(near, "near");
+
+        let parameters = fuzzy.parameters();
+        assert_eq!(parameters, vec![
+            Parameter::InputSet(near, 0),
+            Parameter::InputSet(near, 1),
+            Parameter::OutputSet(slow, 0),
+            Parameter::OutputSet(slow, 1),
+            Parameter::RuleWeight(rule),
+        ]);
+        assert_eq!(fuzzy.parameter_name(parameters[1]), "x.near[1]");
+        assert_eq!(fuzzy.parameter_range(parameters[1]), (0.0, 10.0));
+
+        fuzzy.set_parameters(&parameters[..2], &[5.0, 12.0]);
+        assert_eq!(fuzzy.input_set_function(near).shape(),
+                   Some(&Shape::LeftShoulder { a: 5.0, b: 10.0 }));
+        fuzzy.set_parameters(&parameters[..2], &[5.0, 3.0]);
+        assert_eq!(fuzzy.input_set_function(near).shape(),
+                   Some(&Shape::LeftShoulder { a: 3.0, b: 5.0 }));
+        fuzzy.set_parameters(&parameters[4..], &[2.0]);
+        assert_eq!(fuzzy.rule_weight(rule), 1.0);
+
+        let text = fuzzy.format_parameters();
+        assert_eq!(text.lines().next(), Some("x.near[0] = 3"));
+    }
+}
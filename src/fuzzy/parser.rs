@@ -0,0 +1,556 @@
+use std::fmt;
+
+use super::{Fuzzy, RuleId, OutputSetId, InputId, InputSetId, Antecedent, Hedge};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+// Line 0 marks an error that has no position in the text.
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            return write!(f, "{}", self.message);
+        }
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum TokenKind {
+    Ident(String),
+    Number(f32),
+    Symbol(&'static str),
+    LeftParen,
+    RightParen,
+    End,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct Token {
+    pub kind: TokenKind,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Token {
+    fn keyword(&self) -> Option<String> {
+        match self.kind {
+            TokenKind::Ident(ref name) => Some(name.to_uppercase()),
+            _ => None,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self.kind {
+            TokenKind::Ident(ref name) => format!("`{}`", name),
+            TokenKind::Number(value) => format!("`{}`", value),
+            TokenKind::Symbol(symbol) => format!("`{}`", symbol),
+            TokenKind::LeftParen => "`(`".to_string(),
+            TokenKind::RightParen => "`)`".to_string(),
+            TokenKind::End => "end of input".to_string(),
+        }
+    }
+}
+
+pub(super) fn error(line: usize, column: usize, message: String) -> ParseError {
+    ParseError { line, column, message }
+}
+
+const SYMBOLS: [&str; 7] = [":=", "..", ":", ";", ",", "+", "*"];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, ParseError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let mut line = 1;
+    let mut column = 1;
+
+    let starts_with = |i: usize, s: &str| {
+        s.chars().enumerate().all(|(k, c)| chars.get(i + k) == Some(&c))
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let (start, start_column) = (i, column);
+
+        if c == '\n' {
+            i += 1;
+            line += 1;
+            column = 1;
+            continue;
+        }
+        else if c.is_whitespace() {
+            i += 1;
+        }
+        else if c == '#' || starts_with(i, "//") {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        }
+        else if starts_with(i, "(*") {
+            let start_line = line;
+            i += 2;
+            column += 2;
+            while !starts_with(i, "*)") {
+                if i >= chars.len() {
+                    return Err(error(start_line, start_column,
+                                     "unterminated comment".to_string()));
+                }
+                if chars[i] == '\n' {
+                    line += 1;
+                    column = 0;
+                }
+                i += 1;
+                column += 1;
+            }
+            i += 2;
+            column += 2;
+            continue;
+        }
+        else if c == '(' || c == ')' {
+            i += 1;
+            let kind = if c == '(' { TokenKind::LeftParen } else { TokenKind::RightParen };
+            tokens.push(Token { kind, line, column: start_column });
+        }
+        else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let kind = TokenKind::Ident(chars[start..i].iter().collect());
+            tokens.push(Token { kind, line, column: start_column });
+        }
+        else if c.is_ascii_digit()
+            || (c == '-' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()))
+        {
+            i += 1;
+            while i < chars.len()
+                && (chars[i].is_ascii_digit() || (chars[i] == '.' && !starts_with(i, "..")))
+            {
+                i += 1;
+            }
+            let number: String = chars[start..i].iter().collect();
+            let value = number.parse::<f32>()
+                .map_err(|_| error(line, start_column,
+                                   format!("invalid number `{}`", number)))?;
+            tokens.push(Token { kind: TokenKind::Number(value), line, column: start_column });
+        }
+        else if let Some(symbol) = SYMBOLS.iter().find(|symbol| starts_with(i, symbol)) {
+            i += symbol.len();
+            tokens.push(Token { kind: TokenKind::Symbol(symbol), line, column: start_column });
+        }
+        else {
+            return Err(error(line, start_column,
+                             format!("unexpected character `{}`", c)));
+        }
+
+        column += i - start;
+    }
+
+    tokens.push(Token { kind: TokenKind::End, line, column });
+    Ok(tokens)
+}
+
+pub(super) struct ParsedRule {
+    pub antecedent: Antecedent,
+    pub output_set: OutputSetId,
+    pub weight: f32,
+}
+
+pub(super) struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    pub fn new(text: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            tokens: tokenize(text)?,
+            pos: 0,
+        })
+    }
+
+    pub fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    pub fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != TokenKind::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    pub fn at_keyword(&self, keyword: &str) -> bool {
+        self.peek().keyword().is_some_and(|k| k == keyword)
+    }
+
+    pub fn at_symbol(&self, symbol: &str) -> bool {
+        match self.peek().kind {
+            TokenKind::Symbol(s) => s == symbol,
+            _ => false,
+        }
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.peek().kind == TokenKind::End
+    }
+
+    pub fn unexpected(&self, expected: &str) -> ParseError {
+        let token = self.peek();
+        error(token.line, token.column,
+              format!("expected {}, found {}", expected, token.describe()))
+    }
+
+    pub fn expect_keyword(&mut self, keyword: &str) -> Result<Token, ParseError> {
+        if self.at_keyword(keyword) {
+            Ok(self.next())
+        }
+        else {
+            Err(self.unexpected(&format!("`{}`", keyword)))
+        }
+    }
+
+    pub fn expect_symbol(&mut self, symbol: &str) -> Result<(), ParseError> {
+        if self.at_symbol(symbol) {
+            self.next();
+            Ok(())
+        }
+        else {
+            Err(self.unexpected(&format!("`{}`", symbol)))
+        }
+    }
+
+    pub fn expect_ident(&mut self, what: &str) -> Result<(String, Token), ParseError> {
+        match self.peek().kind {
+            TokenKind::Ident(ref name) => {
+                let name = name.clone();
+                Ok((name, self.next()))
+            },
+            _ => Err(self.unexpected(what)),
+        }
+    }
+
+    pub fn expect_number(&mut self, what: &str) -> Result<f32, ParseError> {
+        match self.peek().kind {
+            TokenKind::Number(value) => {
+                self.next();
+                Ok(value)
+            },
+            _ => Err(self.unexpected(what)),
+        }
+    }
+
+    pub fn rule(&mut self, fuzzy: &Fuzzy) -> Result<ParsedRule, ParseError> {
+        self.expect_keyword("IF")?;
+        let antecedent = self.or_expression(fuzzy)?;
+        self.expect_keyword("THEN")?;
+
+        let (name, token) = self.expect_ident("an output name")?;
+        let output = fuzzy.find_output(&name)
+            .ok_or_else(|| error(token.line, token.column,
+                                 format!("unknown output `{}`", name)))?;
+        self.expect_keyword("IS")?;
+        let (term, token) = self.expect_ident("an output term")?;
+        let output_set = fuzzy.find_output_set(output, &term)
+            .ok_or_else(|| error(token.line, token.column,
+                                 format!("unknown term `{}` of output `{}`", term, name)))?;
+
+        let weight =
+            if self.at_keyword("WITH") {
+                self.next();
+                let token = self.next();
+                match token.kind {
+                    TokenKind::Number(w) if (0.0..=1.0).contains(&w) => w,
+                    TokenKind::Number(w) =>
+                        return Err(error(token.line, token.column,
+                                         format!("rule weight {} is not in [0, 1]", w))),
+                    _ => return Err(error(token.line, token.column,
+                                          format!("expected a rule weight, found {}",
+                                                  token.describe()))),
+                }
+            }
+            else {
+                1.0
+            };
+
+        Ok(ParsedRule { antecedent, output_set, weight })
+    }
+
+    fn or_expression(&mut self, fuzzy: &Fuzzy) -> Result<Antecedent, ParseError> {
+        let mut antecedent = self.and_expression(fuzzy)?;
+        while self.at_keyword("OR") {
+            self.next();
+            antecedent = antecedent.or(self.and_expression(fuzzy)?);
+        }
+        Ok(antecedent)
+    }
+
+    fn and_expression(&mut self, fuzzy: &Fuzzy) -> Result<Antecedent, ParseError> {
+        let mut antecedent = self.unary(fuzzy)?;
+        while self.at_keyword("AND") {
+            self.next();
+            antecedent = antecedent.and(self.unary(fuzzy)?);
+        }
+        Ok(antecedent)
+    }
+
+    fn hedge(&self) -> Option<Hedge> {
+        match self.peek().keyword().as_deref() {
+            Some("VERY") => Some(Hedge::Very),
+            Some("SOMEWHAT") => Some(Hedge::Somewhat),
+            Some("EXTREMELY") => Some(Hedge::Extremely),
+            _ => None,
+        }
+    }
+
+    fn unary(&mut self, fuzzy: &Fuzzy) -> Result<Antecedent, ParseError> {
+        if self.at_keyword("NOT") {
+            self.next();
+            return Ok(!self.unary(fuzzy)?);
+        }
+
+        if let Some(hedge) = self.hedge() {
+            self.next();
+            return Ok(Antecedent::Hedge(hedge, Box::new(self.unary(fuzzy)?)));
+        }
+
+        if self.peek().kind == TokenKind::LeftParen {
+            self.next();
+            let antecedent = self.or_expression(fuzzy)?;
+            if self.peek().kind != TokenKind::RightParen {
+                return Err(self.unexpected("`)`"));
+            }
+            self.next();
+            return Ok(antecedent);
+        }
+
+        let (name, token) = self.expect_ident("an input name")?;
+        let input = fuzzy.find_input(&name)
+            .ok_or_else(|| error(token.line, token.column,
+                                 format!("unknown input `{}`", name)))?;
+        self.expect_keyword("IS")?;
+
+        let negated = self.at_keyword("NOT");
+        if negated {
+            self.next();
+        }
+
+        let mut antecedent = self.hedged_term(fuzzy, input, &name)?;
+        if negated {
+            antecedent = !antecedent;
+        }
+        Ok(antecedent)
+    }
+
+    fn hedged_term(&mut self, fuzzy: &Fuzzy, input: InputId, input_name: &str)
+        -> Result<Antecedent, ParseError>
+    {
+        if let Some(hedge) = self.hedge() {
+            self.next();
+            let term = self.hedged_term(fuzzy, input, input_name)?;
+            return Ok(Antecedent::Hedge(hedge, Box::new(term)));
+        }
+
+        let (term, token) = self.expect_ident("an input term")?;
+        let input_set = fuzzy.find_input_set(input, &term)
+            .ok_or_else(|| error(token.line, token.column,
+                                 format!("unknown term `{}` of input `{}`", term, input_name)))?;
+        Ok(Antecedent::is(input_set))
+    }
+}
+
+fn hedge_name(hedge: Hedge) -> &'static str {
+    match hedge {
+        Hedge::Very => "VERY",
+        Hedge::Somewhat => "SOMEWHAT",
+        Hedge::Extremely => "EXTREMELY",
+    }
+}
+
+fn hedged_input_set(antecedent: &Antecedent, hedges: &mut Vec<Hedge>) -> Option<InputSetId> {
+    match antecedent {
+        Antecedent::Is(id) => Some(*id),
+        Antecedent::Hedge(hedge, term) => {
+            hedges.push(*hedge);
+            hedged_input_set(term, hedges)
+        },
+        _ => None,
+    }
+}
+
+impl Fuzzy {
+    fn parse(&self, text: &str) -> Result<Vec<ParsedRule>, ParseError> {
+        let mut parser = Parser::new(text)?;
+
+        let mut rules = Vec::new();
+        while !parser.at_end() {
+            rules.push(parser.rule(self)?);
+        }
+        Ok(rules)
+    }
+
+    pub fn parse_rules(&mut self, text: &str) -> Result<Vec<RuleId>, ParseError> {
+        let rules = self.parse(text)?;
+        Ok(rules.into_iter()
+           .map(|rule| self.add_weighted_compound_rule(
+                   rule.antecedent, rule.output_set, rule.weight))
+           .collect())
+    }
+
+    fn format_term(&self, input_set: InputSetId, negated: bool, hedges: &[Hedge]) -> String {
+        let set = &self.input_sets[input_set.id];
+        let mut text = format!("{} IS ", self.inputs[set.input.id].name);
+        if negated {
+            text.push_str("NOT ");
+        }
+        for &hedge in hedges.iter() {
+            text.push_str(hedge_name(hedge));
+            text.push(' ');
+        }
+        text.push_str(&set.name);
+        text
+    }
+
+    fn format_antecedent(&self, antecedent: &Antecedent) -> String {
+        let mut hedges = Vec::new();
+        if let Some(id) = hedged_input_set(antecedent, &mut hedges) {
+            return self.format_term(id, false, &hedges);
+        }
+
+        let grouped = |term: &Antecedent| {
+            match term {
+                Antecedent::And(_) | Antecedent::Or(_) =>
+                    format!("({})", self.format_antecedent(term)),
+                _ => self.format_antecedent(term),
+            }
+        };
+
+        match antecedent {
+            Antecedent::And(terms) => {
+                let terms: Vec<String> = terms.iter()
+                    .map(|term| match term {
+                        Antecedent::Or(_) => grouped(term),
+                        _ => self.format_antecedent(term),
+                    })
+                    .collect();
+                terms.join(" AND ")
+            },
+            Antecedent::Or(terms) => {
+                let terms: Vec<String> = terms.iter()
+                    .map(|term| self.format_antecedent(term))
+                    .collect();
+                terms.join(" OR ")
+            },
+            Antecedent::Not(term) => {
+                let mut hedges = Vec::new();
+                match hedged_input_set(term, &mut hedges) {
+                    Some(id) => self.format_term(id, true, &hedges),
+                    None => format!("NOT {}", grouped(term)),
+                }
+            },
+            Antecedent::Hedge(hedge, term) =>
+                format!("{} {}", hedge_name(*hedge), grouped(term)),
+            Antecedent::Is(_) => unreachable!(),
+        }
+    }
+
+    pub fn format_rule(&self, id: RuleId) -> String {
+        debug_assert!(id.id < self.rules.len());
+
+        let rule = &self.rules[id.id];
+        let output_set = &self.output_sets[rule.output_set.id];
+        let mut text = format!(
+            "IF {} THEN {} IS {}",
+            self.format_antecedent(&rule.antecedent),
+            self.outputs[output_set.output.id].name,
+            output_set.name);
+        if rule.weight != 1.0 {
+            text.push_str(&format!(" WITH {}", rule.weight));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Shape;
+
+    use approx::assert_relative_eq;
+
+    fn named_fuzzy() -> Fuzzy {
+        let mut fuzzy = Fuzzy::new();
+
+        let light = fuzzy.add_input(0.0, 1.0);
+        fuzzy.set_input_name(light, "light");
+        let green = fuzzy.add_input_set(light, Shape::LeftShoulder { a: 0.0, b: 1.0 });
+        fuzzy.set_input_set_name(green, "green");
+        let red = fuzzy.add_input_set(light, Shape::RightShoulder { a: 0.0, b: 1.0 });
+        fuzzy.set_input_set_name(red, "red");
+
+        let speed = fuzzy.add_output(0.0, 1.0);
+        fuzzy.set_output_name(speed, "speed");
+        let slow = fuzzy.add_output_set(speed, Shape::Triangle { a: 0.0, b: 0.25, c: 0.5 });
+        fuzzy.set_output_set_name(slow, "slow");
+        let fast = fuzzy.add_output_set(speed, Shape::Triangle { a: 0.5, b: 0.75, c: 1.0 });
+        fuzzy.set_output_set_name(fast, "fast");
+
+        fuzzy
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let mut fuzzy = named_fuzzy();
+        let light = fuzzy.find_input("light").unwrap();
+        let green = fuzzy.find_input_set(light, "green").unwrap();
+        let red = fuzzy.find_input_set(light, "red").unwrap();
+
+        let rules = fuzzy.parse_rules("
+            # comments and blank lines are ignored
+            IF light IS green THEN speed IS fast
+            if (light is red or light is not very green) and not light is green
+                then speed is slow with 0.8
+        ").unwrap();
+        assert_eq!(rules.len(), 2);
+
+        assert_eq!(fuzzy.rules[0].antecedent, Antecedent::Is(green));
+        assert_relative_eq!(fuzzy.rule_weight(rules[0]), 1.0);
+        assert_eq!(
+            fuzzy.rules[1].antecedent,
+            Antecedent::And(vec![
+                Antecedent::Or(vec![
+                    Antecedent::Is(red),
+                    !Antecedent::Is(green).very(),
+                ]),
+                !Antecedent::Is(green),
+            ]));
+        assert_relative_eq!(fuzzy.rule_weight(rules[1]), 0.8);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let mut fuzzy = named_fuzzy();
+
+        let err = fuzzy.parse_rules("IF light IS green THEN speed IS fast\n\
+                                     IF light IS blue THEN speed IS slow").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 13));
+        assert_eq!(err.message, "unknown term `blue` of input `light`");
+        assert!(fuzzy.rules.is_empty());
+
+        let err = fuzzy.parse_rules("IF car_distance IS far THEN speed IS slow").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 4));
+
+        let err = fuzzy.parse_rules("IF light IS green THEN speed IS fast WITH 2").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 43));
+
+        let err = fuzzy.parse_rules("IF light IS green speed IS fast").unwrap_err();
+        assert_eq!(err.to_string(), "1:19: expected `THEN`, found `speed`");
+    }
+}
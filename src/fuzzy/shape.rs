@@ -0,0 +1,393 @@
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Triangle { a: f32, b: f32, c: f32 },
+    Trapezoid { a: f32, b: f32, c: f32, d: f32 },
+    LeftShoulder { a: f32, b: f32 },
+    RightShoulder { a: f32, b: f32 },
+    Gaussian { mean: f32, sigma: f32 },
+    Bell { a: f32, b: f32, c: f32 },
+    Sigmoid { slope: f32, center: f32 },
+    PiecewiseLinear(Vec<(f32, f32)>),
+}
+
+// An empty list of points is zero everywhere.
+fn piecewise_linear(points: &[(f32, f32)], x: f32) -> f32 {
+    if points.is_empty() {
+        return 0.0;
+    }
+
+    let (first_x, first_y) = points[0];
+    let (last_x, last_y) = points[points.len() - 1];
+
+    if x < first_x {
+        return first_y;
+    }
+    if x >= last_x {
+        return last_y;
+    }
+
+    for window in points.windows(2) {
+        let (x1, y1) = window[0];
+        let (x2, y2) = window[1];
+        if x >= x1 && x < x2 {
+            return y1 + (x - x1) * (y2 - y1) / (x2 - x1);
+        }
+    }
+    last_y
+}
+
+// Moves the breakpoints before the first maximum to the left and those after
+// the last maximum to the right, like the feet of a triangle. Breakpoints
+// between the maxima stay.
+fn widened_points(points: &[(f32, f32)], amount: f32) -> Vec<(f32, f32)> {
+    let height = points.iter().fold(f32::NEG_INFINITY, |height, &(_, y)| f32::max(height, y));
+    let first = match points.iter().position(|&(_, y)| y == height) {
+        Some(first) => first,
+        None => return points.to_vec(),
+    };
+    let last = points.iter().rposition(|&(_, y)| y == height).unwrap();
+    let (first_x, last_x) = (points[first].0, points[last].0);
+
+    points.iter().enumerate()
+        .map(|(i, &(x, y))| {
+            if i < first {
+                (f32::min(x - amount, first_x), y)
+            }
+            else if i > last {
+                (f32::max(x + amount, last_x), y)
+            }
+            else {
+                (x, y)
+            }
+        })
+        .collect()
+}
+
+impl Shape {
+    pub fn eval(&self, x: f32) -> f32 {
+        use Shape::*;
+
+        match *self {
+            Triangle { a, b, c } => {
+                if x < a || x > c {
+                    0.0
+                }
+                else if x < b {
+                    (x - a) / (b - a)
+                }
+                else if x > b {
+                    (c - x) / (c - b)
+                }
+                else {
+                    1.0
+                }
+            },
+            Trapezoid { a, b, c, d } => {
+                if x < a || x > d {
+                    0.0
+                }
+                else if x < b {
+                    (x - a) / (b - a)
+                }
+                else if x > c {
+                    (d - x) / (d - c)
+                }
+                else {
+                    1.0
+                }
+            },
+            LeftShoulder { a, b } => {
+                if x <= a {
+                    1.0
+                }
+                else if x >= b {
+                    0.0
+                }
+                else {
+                    (b - x) / (b - a)
+                }
+            },
+            RightShoulder { a, b } => {
+                if x <= a {
+                    0.0
+                }
+                else if x >= b {
+                    1.0
+                }
+                else {
+                    (x - a) / (b - a)
+                }
+            },
+            Gaussian { mean, sigma } => {
+                let d = x - mean;
+                f32::exp(-d * d / (2.0 * sigma * sigma))
+            },
+            Bell { a, b, c } => {
+                1.0 / (1.0 + f32::powf(f32::abs((x - c) / a), 2.0 * b))
+            },
+            Sigmoid { slope, center } => {
+                1.0 / (1.0 + f32::exp(-slope * (x - center)))
+            },
+            PiecewiseLinear(ref points) => piecewise_linear(points, x),
+        }
+    }
+
+    pub fn peak(&self) -> Option<f32> {
+        use Shape::*;
+
+        match *self {
+            Triangle { b, .. } => Some(b),
+            Trapezoid { b, c, .. } => Some((b + c) / 2.0),
+            Gaussian { mean, .. } => Some(mean),
+            Bell { c, .. } => Some(c),
+            _ => None,
+        }
+    }
+
+    pub fn linear_points(&self) -> Option<Vec<(f32, f32)>> {
+        use Shape::*;
+
+        match *self {
+            Triangle { a, b, c } => Some(vec![(a, 0.0), (b, 1.0), (c, 0.0)]),
+            Trapezoid { a, b, c, d } =>
+                Some(vec![(a, 0.0), (b, 1.0), (c, 1.0), (d, 0.0)]),
+            LeftShoulder { a, b } => Some(vec![(a, 1.0), (b, 0.0)]),
+            RightShoulder { a, b } => Some(vec![(a, 0.0), (b, 1.0)]),
+            PiecewiseLinear(ref points) => Some(points.clone()),
+            Gaussian { .. } | Bell { .. } | Sigmoid { .. } => None,
+        }
+    }
+
+    pub fn widened(&self, amount: f32) -> Shape {
+        use Shape::*;
+
+        match *self {
+            Triangle { a, b, c } => Triangle {
+                a: f32::min(a - amount, b),
+                b,
+                c: f32::max(c + amount, b),
+            },
+            Trapezoid { a, b, c, d } => Trapezoid {
+                a: f32::min(a - amount, b),
+                b,
+                c,
+                d: f32::max(d + amount, c),
+            },
+            LeftShoulder { a, b } => LeftShoulder { a, b: f32::max(b + amount, a) },
+            RightShoulder { a, b } => RightShoulder { a: f32::min(a - amount, b), b },
+            Gaussian { mean, sigma } =>
+                Gaussian { mean, sigma: f32::max(sigma + amount, f32::EPSILON) },
+            Bell { a, b, c } => Bell { a: f32::max(a + amount, f32::EPSILON), b, c },
+            Sigmoid { slope, center } =>
+                Sigmoid { slope, center: center - amount * slope.signum() },
+            PiecewiseLinear(ref points) => PiecewiseLinear(widened_points(points, amount)),
+        }
+    }
+
+    // Restores the invariants of the parameters after they were moved
+    // independently, e.g. by a tuner.
+    pub fn repair(&mut self) {
+        use Shape::*;
+
+        let mut params = self.parameters();
+        match self {
+            Triangle { .. } | Trapezoid { .. } | LeftShoulder { .. } | RightShoulder { .. } =>
+                params.sort_by(|a, b| a.partial_cmp(b).unwrap()),
+            Gaussian { .. } => params[1] = f32::max(params[1].abs(), f32::EPSILON),
+            Bell { .. } => params[0] = f32::max(params[0].abs(), f32::EPSILON),
+            Sigmoid { .. } | PiecewiseLinear(_) => (),
+        }
+        self.set_parameters(&params);
+    }
+
+    pub fn parameters(&self) -> Vec<f32> {
+        use Shape::*;
+
+        match *self {
+            Triangle { a, b, c } => vec![a, b, c],
+            Trapezoid { a, b, c, d } => vec![a, b, c, d],
+            LeftShoulder { a, b } => vec![a, b],
+            RightShoulder { a, b } => vec![a, b],
+            Gaussian { mean, sigma } => vec![mean, sigma],
+            Bell { a, b, c } => vec![a, b, c],
+            Sigmoid { slope, center } => vec![slope, center],
+            PiecewiseLinear(ref points) =>
+                points.iter().flat_map(|&(x, y)| vec![x, y]).collect(),
+        }
+    }
+
+    pub fn set_parameters(&mut self, params: &[f32]) {
+        use Shape::*;
+
+        debug_assert_eq!(params.len(), self.parameters().len());
+
+        match *self {
+            Triangle { ref mut a, ref mut b, ref mut c } => {
+                *a = params[0];
+                *b = params[1];
+                *c = params[2];
+            },
+            Trapezoid { ref mut a, ref mut b, ref mut c, ref mut d } => {
+                *a = params[0];
+                *b = params[1];
+                *c = params[2];
+                *d = params[3];
+            },
+            LeftShoulder { ref mut a, ref mut b }
+            | RightShoulder { ref mut a, ref mut b } => {
+                *a = params[0];
+                *b = params[1];
+            },
+            Gaussian { ref mut mean, ref mut sigma } => {
+                *mean = params[0];
+                *sigma = params[1];
+            },
+            Bell { ref mut a, ref mut b, ref mut c } => {
+                *a = params[0];
+                *b = params[1];
+                *c = params[2];
+            },
+            Sigmoid { ref mut slope, ref mut center } => {
+                *slope = params[0];
+                *center = params[1];
+            },
+            PiecewiseLinear(ref mut points) => {
+                for (point, pair) in points.iter_mut().zip(params.chunks(2)) {
+                    *point = (pair[0], pair[1]);
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_triangle_and_trapezoid() {
+        let triangle = Shape::Triangle { a: 1.0, b: 3.0, c: 4.0 };
+        assert_relative_eq!(triangle.eval(0.5), 0.0);
+        assert_relative_eq!(triangle.eval(2.0), 0.5);
+        assert_relative_eq!(triangle.eval(3.0), 1.0);
+        assert_relative_eq!(triangle.eval(3.5), 0.5);
+        assert_relative_eq!(triangle.eval(5.0), 0.0);
+
+        let trapezoid = Shape::Trapezoid { a: 5.0, b: 10.0, c: 25.0, d: 30.0 };
+        assert_relative_eq!(trapezoid.eval(7.5), 0.5);
+        assert_relative_eq!(trapezoid.eval(20.0), 1.0);
+        assert_relative_eq!(trapezoid.eval(29.0), 0.2);
+        assert_eq!(trapezoid.peak(), Some(17.5));
+    }
+
+    #[test]
+    fn test_shoulders() {
+        let left = Shape::LeftShoulder { a: 5.0, b: 10.0 };
+        assert_relative_eq!(left.eval(-100.0), 1.0);
+        assert_relative_eq!(left.eval(7.5), 0.5);
+        assert_relative_eq!(left.eval(10.0), 0.0);
+
+        let right = Shape::RightShoulder { a: 20.0, b: 25.0 };
+        assert_relative_eq!(right.eval(20.0), 0.0);
+        assert_relative_eq!(right.eval(24.0), 0.8);
+        assert_relative_eq!(right.eval(1000.0), 1.0);
+    }
+
+    #[test]
+    fn test_smooth_shapes() {
+        let gaussian = Shape::Gaussian { mean: 1.0, sigma: 2.0 };
+        assert_relative_eq!(gaussian.eval(1.0), 1.0);
+        assert_relative_eq!(gaussian.eval(3.0), f32::exp(-0.5));
+
+        let bell = Shape::Bell { a: 2.0, b: 1.0, c: 0.0 };
+        assert_relative_eq!(bell.eval(0.0), 1.0);
+        assert_relative_eq!(bell.eval(2.0), 0.5);
+
+        let sigmoid = Shape::Sigmoid { slope: 4.0, center: 1.0 };
+        assert_relative_eq!(sigmoid.eval(1.0), 0.5);
+        assert!(sigmoid.eval(3.0) > 0.99);
+    }
+
+    #[test]
+    fn test_piecewise_linear() {
+        let yellow = Shape::PiecewiseLinear(vec![
+            (2.0, 0.0), (3.0, 1.0), (4.0, 0.0),
+            (6.0, 0.0), (7.0, 1.0), (8.0, 0.0),
+        ]);
+        assert_relative_eq!(yellow.eval(1.0), 0.0);
+        assert_relative_eq!(yellow.eval(2.5), 0.5);
+        assert_relative_eq!(yellow.eval(5.0), 0.0);
+        assert_relative_eq!(yellow.eval(7.0), 1.0);
+
+        let step = Shape::PiecewiseLinear(vec![(0.7, 1.0), (0.7, 0.0)]);
+        assert_relative_eq!(step.eval(0.69), 1.0);
+        assert_relative_eq!(step.eval(0.7), 0.0);
+
+        let trapezoid = Shape::Trapezoid { a: 5.0, b: 10.0, c: 25.0, d: 30.0 };
+        let points = Shape::PiecewiseLinear(trapezoid.linear_points().unwrap());
+        for &x in [0.0, 7.5, 20.0, 29.0, 40.0].iter() {
+            assert_relative_eq!(points.eval(x), trapezoid.eval(x));
+        }
+        assert_eq!(Shape::Gaussian { mean: 0.0, sigma: 1.0 }.linear_points(), None);
+
+        let empty = Shape::PiecewiseLinear(vec![]);
+        assert_relative_eq!(empty.eval(0.0), 0.0);
+        assert_relative_eq!(empty.eval(f32::INFINITY), 0.0);
+        assert_eq!(empty.peak(), None);
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut shape = Shape::Triangle { a: 0.6, b: 0.4, c: 1.0 };
+        shape.repair();
+        assert_eq!(shape, Shape::Triangle { a: 0.4, b: 0.6, c: 1.0 });
+
+        let mut shape = Shape::Gaussian { mean: 0.0, sigma: -0.5 };
+        shape.repair();
+        assert_eq!(shape, Shape::Gaussian { mean: 0.0, sigma: 0.5 });
+    }
+
+    #[test]
+    fn test_widened() {
+        let triangle = Shape::Triangle { a: 1.0, b: 3.0, c: 4.0 };
+        assert_eq!(triangle.widened(1.0), Shape::Triangle { a: 0.0, b: 3.0, c: 5.0 });
+        assert_eq!(triangle.widened(-1.5), Shape::Triangle { a: 2.5, b: 3.0, c: 3.0 });
+
+        let left = Shape::LeftShoulder { a: 5.0, b: 10.0 };
+        assert_eq!(left.widened(2.0), Shape::LeftShoulder { a: 5.0, b: 12.0 });
+        assert_eq!(left.widened(-10.0), Shape::LeftShoulder { a: 5.0, b: 5.0 });
+
+        let sigmoid = Shape::Sigmoid { slope: -4.0, center: 1.0 };
+        assert_eq!(sigmoid.widened(0.5), Shape::Sigmoid { slope: -4.0, center: 1.5 });
+        assert!(sigmoid.widened(-0.5).eval(1.0) < sigmoid.widened(0.5).eval(1.0));
+
+        let points = Shape::PiecewiseLinear(vec![(1.0, 0.0), (2.0, 1.0), (3.0, 1.0), (4.0, 0.0)]);
+        assert_eq!(points.widened(0.5), Shape::PiecewiseLinear(
+            vec![(0.5, 0.0), (2.0, 1.0), (3.0, 1.0), (4.5, 0.0)]));
+        assert_eq!(points.widened(-2.0), Shape::PiecewiseLinear(
+            vec![(2.0, 0.0), (2.0, 1.0), (3.0, 1.0), (3.0, 0.0)]));
+        assert_eq!(Shape::PiecewiseLinear(vec![]).widened(1.0), Shape::PiecewiseLinear(vec![]));
+
+        let gaussian = Shape::Gaussian { mean: 1.0, sigma: 2.0 };
+        assert_eq!(gaussian.widened(0.5), Shape::Gaussian { mean: 1.0, sigma: 2.5 });
+        for &x in [-2.0, 0.0, 1.0, 4.0].iter() {
+            assert!(gaussian.widened(-0.5).eval(x) <= gaussian.widened(0.5).eval(x));
+        }
+    }
+
+    #[test]
+    fn test_parameters() {
+        let mut shape = Shape::Triangle { a: 0.0, b: 1.0, c: 2.0 };
+        assert_eq!(shape.parameters(), vec![0.0, 1.0, 2.0]);
+
+        shape.set_parameters(&[1.0, 2.0, 3.0]);
+        assert_eq!(shape, Shape::Triangle { a: 1.0, b: 2.0, c: 3.0 });
+
+        let mut shape = Shape::PiecewiseLinear(vec![(0.0, 1.0), (1.0, 0.0)]);
+        assert_eq!(shape.parameters(), vec![0.0, 1.0, 1.0, 0.0]);
+        shape.set_parameters(&[0.5, 1.0, 1.5, 0.0]);
+        assert_relative_eq!(shape.eval(1.0), 0.5);
+    }
+}
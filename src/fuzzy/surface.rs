@@ -0,0 +1,214 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use super::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ColorMap {
+    Grayscale,
+    Heat,
+}
+
+#[derive(Clone, Debug)]
+pub struct Surface {
+    pub x: InputId,
+    pub y: InputId,
+    pub output: OutputId,
+    pub xs: Vec<f32>,
+    pub ys: Vec<f32>,
+    pub values: Vec<f32>,
+    pub min: f32,
+    pub max: f32,
+}
+
+fn linspace(min: f32, max: f32, samples: usize) -> Vec<f32> {
+    let step = (max - min) / (samples - 1) as f32;
+    (0..samples).map(|i| min + step * i as f32).collect()
+}
+
+fn heat(t: f32) -> [u8; 3] {
+    const STOPS: [[f32; 3]; 5] = [
+        [0.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0],
+        [0.0, 1.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [1.0, 0.0, 0.0],
+    ];
+    let scaled = t * (STOPS.len() - 1) as f32;
+    let i = (scaled.floor() as usize).min(STOPS.len() - 2);
+    let s = scaled - i as f32;
+    let mut color = [0; 3];
+    for (k, c) in color.iter_mut().enumerate() {
+        let (c0, c1) = (STOPS[i][k], STOPS[i + 1][k]);
+        *c = ((c0 + (c1 - c0) * s) * 255.0).round() as u8;
+    }
+    color
+}
+
+impl Surface {
+    pub fn value(&self, i: usize, j: usize) -> f32 {
+        self.values[j * self.xs.len() + i]
+    }
+
+    pub fn color(&self, value: f32, color_map: ColorMap) -> [u8; 3] {
+        if value.is_nan() {
+            return [0, 0, 0];
+        }
+        let t = if self.max > self.min {
+            ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        }
+        else {
+            0.0
+        };
+        match color_map {
+            ColorMap::Grayscale => {
+                let gray = (t * 255.0).round() as u8;
+                [gray, gray, gray]
+            },
+            ColorMap::Heat => heat(t),
+        }
+    }
+
+    pub fn save_image<P: AsRef<Path>>(
+        &self, path: P, color_map: ColorMap)
+        -> std::io::Result<()>
+    {
+        let width = self.xs.len() as u32;
+        let height = self.ys.len() as u32;
+        let image = image::RgbImage::from_fn(width, height, |i, row| {
+            let j = height - 1 - row;
+            image::Rgb(self.color(self.value(i as usize, j as usize), color_map))
+        });
+        image.save(path)
+    }
+}
+
+impl Fuzzy {
+    pub fn control_surface(
+        &self, rule_set: RuleSetId, output: OutputId,
+        axes: (InputId, InputId), inputs: &[f32], samples: usize)
+        -> Result<Surface, FuzzyError>
+    {
+        if samples < 2 {
+            return Err(FuzzyError::TooFewSamples { samples });
+        }
+        let (x, y) = axes;
+        if output.id >= self.outputs.len() {
+            return Err(FuzzyError::UnknownOutput(output));
+        }
+        for &id in &[x, y] {
+            if id.id >= self.inputs.len() {
+                return Err(FuzzyError::UnknownInput(id));
+            }
+        }
+        if inputs.len() != self.inputs.len() {
+            return Err(FuzzyError::InputCount {
+                expected: self.inputs.len(),
+                found: inputs.len(),
+            });
+        }
+
+        let (x_min, x_max) = self.sample_range(x);
+        let (y_min, y_max) = self.sample_range(y);
+        let xs = linspace(x_min, x_max, samples);
+        let ys = linspace(y_min, y_max, samples);
+
+        let mut point = inputs.to_vec();
+        let mut scratch = Scratch::default();
+        let mut values = Vec::with_capacity(xs.len() * ys.len());
+        for &y_value in &ys {
+            for &x_value in &xs {
+                point[x.id] = x_value;
+                point[y.id] = y_value;
+                // Each point stands on its own, whatever the sweep held before.
+                scratch.forget_held();
+                let evaluation = self.evaluate_with(rule_set, &point, &mut scratch)?;
+                values.push(evaluation.value(output).unwrap_or(f32::NAN));
+            }
+        }
+
+        let range = &self.outputs[output.id];
+        Ok(Surface {
+            x, y, output, xs, ys, values,
+            min: range.min,
+            max: range.max,
+        })
+    }
+
+    pub fn surface_csv(&self, surface: &Surface) -> String {
+        let mut text = String::new();
+        writeln!(text, "{},{},{}",
+            self.input_name(surface.x),
+            self.input_name(surface.y),
+            self.output_name(surface.output)).unwrap();
+        for (j, y) in surface.ys.iter().enumerate() {
+            for (i, x) in surface.xs.iter().enumerate() {
+                writeln!(text, "{},{},{}", x, y, surface.value(i, j)).unwrap();
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_control_surface() {
+        let mut fuzzy = Fuzzy::new();
+        let a = fuzzy.add_input(0.0, 1.0);
+        let b = fuzzy.add_input(0.0, 2.0);
+        let unused = fuzzy.add_input(0.0, 1.0);
+        let a_high = fuzzy.add_input_set(a, Shape::RightShoulder { a: 0.0, b: 1.0 });
+        let b_high = fuzzy.add_input_set(b, Shape::RightShoulder { a: 0.0, b: 2.0 });
+        let out = fuzzy.add_sugeno_output(0.0, 3.0);
+        let sum = fuzzy.add_consequent(out, Consequent::Linear {
+            coefficients: vec![(a, 1.0), (b, 1.0)],
+            constant: 0.0,
+        });
+        fuzzy.set_input_name(a, "a");
+        fuzzy.set_input_name(b, "b");
+        fuzzy.set_output_name(out, "out");
+        let rule = fuzzy.add_compound_rule(
+            Antecedent::is(a_high).or(Antecedent::is(b_high)), sum);
+        let rule_set = fuzzy.add_rule_set(&[rule]);
+
+        let inputs = fuzzy.input_values();
+        let surface = fuzzy.control_surface(rule_set, out, (a, b), &inputs, 3).unwrap();
+        assert_eq!(surface.xs, vec![0.0, 0.5, 1.0]);
+        assert_eq!(surface.ys, vec![0.0, 1.0, 2.0]);
+        assert!(surface.value(0, 0).is_nan());
+        assert_relative_eq!(surface.value(1, 0), 0.5);
+        assert_relative_eq!(surface.value(2, 2), 3.0);
+        assert_relative_eq!(surface.value(0, 1), 1.0);
+
+        assert_eq!(surface.color(3.0, ColorMap::Grayscale), [255, 255, 255]);
+        assert_eq!(surface.color(1.5, ColorMap::Heat), [0, 255, 0]);
+        assert_eq!(surface.color(f32::NAN, ColorMap::Heat), [0, 0, 0]);
+
+        let csv = fuzzy.surface_csv(&surface);
+        assert!(csv.starts_with("a,b,out\n0,0,NaN\n0.5,0,0.5\n"));
+        assert_eq!(csv.lines().count(), 10);
+
+        assert_eq!(
+            fuzzy.control_surface(rule_set, out, (a, InputId { id: 5 }), &inputs, 3).err(),
+            Some(FuzzyError::UnknownInput(InputId { id: 5 })));
+        assert!(fuzzy.control_surface(rule_set, out, (a, unused), &inputs, 3).is_ok());
+        assert_eq!(
+            fuzzy.control_surface(rule_set, out, (a, b), &inputs[..1], 3).err(),
+            Some(FuzzyError::InputCount { expected: 3, found: 1 }));
+        assert_eq!(
+            fuzzy.control_surface(rule_set, out, (a, b), &inputs, 1).err(),
+            Some(FuzzyError::TooFewSamples { samples: 1 }));
+
+        // No rule fires at a = 0 with b = 0, which holds the same value on
+        // every row however the sweep went before.
+        fuzzy.set_fallback(out, Fallback::HoldPrevious);
+        let surface = fuzzy.control_surface(rule_set, out, (a, unused), &inputs, 3).unwrap();
+        for j in 0..3 {
+            assert_eq!(surface.value(0, j), 0.0);
+        }
+    }
+}
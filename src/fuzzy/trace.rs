@@ -0,0 +1,181 @@
+use std::fmt;
+
+use super::*;
+use super::math::{Activation, collect_dirty_input_sets, compute_input_membership, sample_output};
+
+#[derive(Clone, Debug)]
+pub struct InputTrace {
+    pub input: InputId,
+    pub name: String,
+    pub value: f32,
+    pub memberships: Vec<(String, f32)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RuleTrace {
+    pub rule: RuleId,
+    pub text: String,
+    pub strength: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct OutputTrace {
+    pub name: String,
+    pub aggregate: Vec<(f32, f32)>,
+    pub value: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Trace {
+    pub rule_set: String,
+    pub inputs: Vec<InputTrace>,
+    pub rules: Vec<RuleTrace>,
+    pub outputs: Vec<OutputTrace>,
+}
+
+impl Fuzzy {
+    pub fn trace(&self) -> Option<Trace> {
+        self.trace_with(&self.scratch)
+    }
+
+    pub fn trace_with(&self, scratch: &Scratch) -> Option<Trace> {
+        let rule_set = scratch.rule_set()?;
+        let operators = self.rule_sets[rule_set.id].operators;
+
+        let input_sets = collect_dirty_input_sets(&self.rule_sets, &self.rules, rule_set);
+        let mut inputs: Vec<InputTrace> = Vec::new();
+        for &id in input_sets.iter() {
+            let set = &self.input_sets[id.id];
+            if !inputs.iter().any(|input| input.input == set.input) {
+                inputs.push(InputTrace {
+                    input: set.input,
+                    name: self.inputs[set.input.id].name.clone(),
+                    value: scratch.input_value(set.input),
+                    memberships: Vec::new(),
+                });
+            }
+            let input = inputs.iter_mut().find(|input| input.input == set.input).unwrap();
+            input.memberships.push((set.name.clone(), scratch.input_set_membership(id)));
+        }
+        inputs.sort_by_key(|input| input.input.id);
+
+        let rules = self.rule_sets[rule_set.id].rules.iter()
+            .map(|&rule| RuleTrace {
+                rule,
+                text: self.format_rule(rule),
+                strength: compute_input_membership(
+                    &self.rules, scratch.input_memberships(), rule, operators),
+            })
+            .collect();
+
+        let outputs = self.outputs.iter().enumerate()
+            .filter(|&(id, _)| scratch.is_active(OutputId { id }))
+            .map(|(id, output)| {
+                let aggregate =
+                    match output.kind {
+                        OutputKind::Mamdani => sample_output(
+                            Activation::new(self, scratch, OutputId { id }),
+                            operators.accumulation),
+                        OutputKind::Sugeno => Vec::new(),
+                    };
+                OutputTrace {
+                    name: output.name.clone(),
+                    aggregate,
+                    value: scratch.output_value(OutputId { id }),
+                }
+            })
+            .collect();
+
+        Some(Trace {
+            rule_set: self.rule_set_name(rule_set).to_string(),
+            inputs,
+            rules,
+            outputs,
+        })
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "rule set {}", self.rule_set)?;
+
+        writeln!(f, "inputs:")?;
+        for input in self.inputs.iter() {
+            let memberships: Vec<String> = input.memberships.iter()
+                .map(|(name, membership)| format!("{} {:.3}", name, membership))
+                .collect();
+            writeln!(f, "    {} = {} ({})", input.name, input.value, memberships.join(", "))?;
+        }
+
+        writeln!(f, "fired rules:")?;
+        for rule in self.rules.iter().filter(|rule| rule.strength > 0.0) {
+            writeln!(f, "    {:.3}  rule {}: {}", rule.strength, rule.rule.id, rule.text)?;
+        }
+
+        writeln!(f, "outputs:")?;
+        for output in self.outputs.iter() {
+            write!(f, "    {} = {}", output.name, output.value)?;
+            // Where the clipped sets reach highest, for Mamdani outputs.
+            let peak = output.aggregate.iter()
+                .fold(None, |peak: Option<(f32, f32)>, &(x, y)| match peak {
+                    Some((_, top)) if top >= y => peak,
+                    _ => Some((x, y)),
+                });
+            match peak {
+                Some((x, y)) => writeln!(f, " (aggregate peaks at {} with {:.3})", x, y)?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_trace() {
+        let mut fuzzy = Fuzzy::new();
+        assert!(fuzzy.trace().is_none());
+
+        let light = fuzzy.add_input(0.0, 1.0);
+        let green = fuzzy.add_input_set(light, Shape::LeftShoulder { a: 0.0, b: 1.0 });
+        let red = fuzzy.add_input_set(light, Shape::RightShoulder { a: 0.0, b: 1.0 });
+        let speed = fuzzy.add_output(0.0, 1.0);
+        let slow = fuzzy.add_output_set(speed, Shape::Triangle { a: 0.0, b: 0.25, c: 0.5 });
+        let fast = fuzzy.add_output_set(speed, Shape::Triangle { a: 0.5, b: 0.75, c: 1.0 });
+        fuzzy.set_input_name(light, "light");
+        fuzzy.set_input_set_name(red, "red");
+        fuzzy.set_output_name(speed, "speed");
+        fuzzy.set_output_set_name(slow, "slow");
+
+        let rule1 = fuzzy.add_rule(&[green], fast);
+        let rule2 = fuzzy.add_rule(&[red], slow);
+        let rule_set = fuzzy.add_rule_set(&[rule1, rule2]);
+
+        fuzzy.set_input(light, 0.8).unwrap();
+        fuzzy.evaluate(rule_set).unwrap();
+        let trace = fuzzy.trace().unwrap();
+
+        assert_eq!(trace.inputs.len(), 1);
+        assert_relative_eq!(trace.inputs[0].value, 0.8);
+        assert_eq!(trace.inputs[0].memberships[1].0, "red");
+        assert_relative_eq!(trace.inputs[0].memberships[1].1, 0.8);
+
+        assert_relative_eq!(trace.rules[0].strength, 0.2, epsilon = 1e-6);
+        assert_relative_eq!(trace.rules[1].strength, 0.8);
+        assert_eq!(trace.rules[1].text, "IF light IS red THEN speed IS slow");
+
+        let output = &trace.outputs[0];
+        assert_relative_eq!(output.value, fuzzy.get_output(speed).unwrap());
+        let peak = output.aggregate.iter().cloned().fold(0.0, |m, (_, y)| f32::max(m, y));
+        assert_relative_eq!(peak, 0.8);
+
+        let text = trace.to_string();
+        assert!(text.contains("0.800  rule 1: IF light IS red THEN speed IS slow"));
+        assert!(text.contains("speed = 0.3863637 (aggregate peaks at 0.2 with 0.800)"));
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt::Write;
+
+use super::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtractedRule {
+    pub rule: RuleId,
+    pub degree: f32,
+    pub support: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Extraction {
+    pub rule_set: RuleSetId,
+    pub rules: Vec<ExtractedRule>,
+    pub uncovered: usize,
+}
+
+struct Candidate {
+    input_sets: Vec<InputSetId>,
+    output_set: OutputSetId,
+    degree: f32,
+    support: usize,
+}
+
+fn best_set<I>(sets: I) -> Option<(usize, f32)>
+    where I: Iterator<Item = (usize, f32)>
+{
+    sets.filter(|&(_, membership)| membership > 0.0)
+        .fold(None, |best, (id, membership)| match best {
+            Some((_, best_membership)) if best_membership >= membership => best,
+            _ => Some((id, membership)),
+        })
+}
+
+impl Fuzzy {
+    fn best_input_set(&self, input: InputId, value: f32) -> Option<(InputSetId, f32)> {
+        best_set(self.input_sets.iter().enumerate()
+            .filter(|(_, set)| set.input == input)
+            .map(|(id, set)| (id, set.f.eval(value))))
+            .map(|(id, membership)| (InputSetId { id }, membership))
+    }
+
+    fn best_output_set(&self, output: OutputId, value: f32) -> Option<(OutputSetId, f32)> {
+        best_set(self.output_sets.iter().enumerate()
+            .filter(|(_, set)| set.output == output)
+            .filter_map(|(id, set)| set.term.membership().map(|f| (id, f.eval(value)))))
+            .map(|(id, membership)| (OutputSetId { id }, membership))
+    }
+
+    // Wang-Mendel: every sample proposes one rule per output from the sets
+    // it belongs to most, with the product of those memberships as degree.
+    // Of the rules sharing an antecedent, the one with the highest degree
+    // is kept.
+    pub fn extract_rules(
+        &mut self, inputs: &[InputId], outputs: &[OutputId], samples: &[Sample])
+        -> Result<Extraction, FuzzyError>
+    {
+        for &input in inputs.iter() {
+            if input.id >= self.inputs.len() {
+                return Err(FuzzyError::UnknownInput(input));
+            }
+        }
+        for &output in outputs.iter() {
+            match self.outputs.get(output.id) {
+                None => return Err(FuzzyError::UnknownOutput(output)),
+                Some(o) if o.kind != OutputKind::Mamdani =>
+                    return Err(FuzzyError::NotMamdani { output: o.name.clone() }),
+                Some(_) => (),
+            }
+        }
+
+        let mut candidates: Vec<Candidate> = Vec::new();
+        let mut uncovered = 0;
+        for sample in samples.iter() {
+            let antecedent: Option<Vec<(InputSetId, f32)>> = inputs.iter()
+                .map(|&input| self.best_input_set(input, sample.inputs[input.id]))
+                .collect();
+            let antecedent = match antecedent {
+                Some(antecedent) => antecedent,
+                None => {
+                    uncovered += 1;
+                    continue;
+                },
+            };
+            let input_sets: Vec<InputSetId> = antecedent.iter().map(|&(id, _)| id).collect();
+            let strength: f32 = antecedent.iter().map(|&(_, membership)| membership).product();
+
+            for &output in outputs.iter() {
+                let (output_set, membership) = match sample.output(output)
+                    .and_then(|value| self.best_output_set(output, value))
+                {
+                    Some(best) => best,
+                    None => continue,
+                };
+                let degree = strength * membership;
+
+                let region = candidates.iter_mut().find(|candidate| {
+                    candidate.input_sets == input_sets
+                        && self.output_sets[candidate.output_set.id].output == output
+                });
+                match region {
+                    Some(candidate) => {
+                        candidate.support += 1;
+                        if degree > candidate.degree {
+                            candidate.output_set = output_set;
+                            candidate.degree = degree;
+                        }
+                    },
+                    None => candidates.push(Candidate {
+                        input_sets: input_sets.clone(),
+                        output_set,
+                        degree,
+                        support: 1,
+                    }),
+                }
+            }
+        }
+
+        let rules: Vec<ExtractedRule> = candidates.iter()
+            .map(|candidate| ExtractedRule {
+                rule: self.add_rule(&candidate.input_sets, candidate.output_set),
+                degree: candidate.degree,
+                support: candidate.support,
+            })
+            .collect();
+        let ids: Vec<RuleId> = rules.iter().map(|rule| rule.rule).collect();
+        let rule_set = self.add_rule_set(&ids);
+
+        Ok(Extraction { rule_set, rules, uncovered })
+    }
+
+    pub fn format_extraction(&self, extraction: &Extraction) -> String {
+        let mut text = String::new();
+        writeln!(text, "{} rules in {}", extraction.rules.len(),
+            self.rule_sets[extraction.rule_set.id].name).unwrap();
+        for rule in extraction.rules.iter() {
+            writeln!(text, "    {}  (degree {:.3}, {} samples)",
+                self.format_rule(rule.rule), rule.degree, rule.support).unwrap();
+        }
+        if extraction.uncovered > 0 {
+            writeln!(text, "{} samples are not covered by the input sets",
+                extraction.uncovered).unwrap();
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_rules() {
+        let mut fuzzy = Fuzzy::new();
+        let distance = fuzzy.add_input(0.0, 10.0);
+        let light = fuzzy.add_input(0.0, 1.0);
+        let near = fuzzy.add_input_set(distance, Shape::LeftShoulder { a: 2.0, b: 8.0 });
+        let far = fuzzy.add_input_set(distance, Shape::RightShoulder { a: 2.0, b: 8.0 });
+        let red = fuzzy.add_input_set(light, Shape::LeftShoulder { a: 0.0, b: 0.5 });
+        let speed = fuzzy.add_output(0.0, 1.0);
+        let slow = fuzzy.add_output_set(speed, Shape::LeftShoulder { a: 0.0, b: 1.0 });
+        let fast = fuzzy.add_output_set(speed, Shape::RightShoulder { a: 0.0, b: 1.0 });
+        let sugeno = fuzzy.add_sugeno_output(0.0, 1.0);
+        fuzzy.set_input_name(distance, "distance");
+        fuzzy.set_input_name(light, "light");
+        fuzzy.set_input_set_name(near, "near");
+        fuzzy.set_input_set_name(far, "far");
+        fuzzy.set_input_set_name(red, "red");
+        fuzzy.set_output_name(speed, "speed");
+        fuzzy.set_output_set_name(slow, "slow");
+        fuzzy.set_output_set_name(fast, "fast");
+
+        let samples = fuzzy.read_samples("distance,light,speed\n\
+            1,0,0.1\n\
+            4,0,0.8\n\
+            3,0,0.3\n\
+            9,0,0.9\n\
+            9,0.9,0.9\n").unwrap();
+        let extraction = fuzzy.extract_rules(&[distance, light], &[speed], &samples).unwrap();
+
+        assert_eq!(extraction.rules.len(), 2);
+        assert_eq!(extraction.uncovered, 1);
+        assert_eq!(extraction.rules[0].support, 3);
+        assert_eq!(extraction.rules[1].support, 1);
+        assert_eq!(fuzzy.format_rule(extraction.rules[0].rule),
+                   "IF distance IS near AND light IS red THEN speed IS slow");
+        assert_eq!(fuzzy.format_rule(extraction.rules[1].rule),
+                   "IF distance IS far AND light IS red THEN speed IS fast");
+
+        let text = fuzzy.format_extraction(&extraction);
+        assert!(text.starts_with("2 rules in rules0\n"));
+        assert!(text.contains("THEN speed IS slow  (degree 0.900, 3 samples)\n"));
+        assert!(text.ends_with("1 samples are not covered by the input sets\n"));
+
+        let evaluation = fuzzy.evaluate(extraction.rule_set).unwrap();
+        assert!(evaluation.value(speed).is_some());
+
+        assert_eq!(
+            fuzzy.extract_rules(&[distance], &[sugeno], &samples),
+            Err(FuzzyError::NotMamdani { output: "output1".to_string() }));
+    }
+}
@@ -12,6 +12,7 @@ mod reducer;
 mod fuzzy;
 mod init;
 mod context;
+mod cli;
 
 use std::time::Instant;
 
@@ -26,6 +27,15 @@ fn main() {
     use glium::glutin;
     use glium::Surface;
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(err) = cli::run(&args) {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let mut events_loop = glutin::EventsLoop::new();
     let window = 
         glutin::WindowBuilder::new()
@@ -81,6 +81,14 @@ pub fn reduce(
                 context.car_system.add_car = Adding;
                 context.car_system.add_car_type = CarType::Slow;
             },
+            Action::Trace => {
+                if context.car_system.chosen_car.is_some() {
+                    context.car_system.trace_requested = true;
+                }
+                else {
+                    println!("Warning: No car is chosen to trace");
+                }
+            },
         };
     }
 }
@@ -10,6 +10,8 @@ use bezier::Line;
 
 use std::time::{Instant};
 
+use rand::Rng;
+
 use std::collections::BinaryHeap;
 use std::cmp::Ordering;
 
@@ -186,13 +188,13 @@ pub struct Backbone {
     pub cross_sections: Vec<CrossSectionBackbone>,
 }
 
-fn random_green_time(config: &Config) -> f32 {
+fn random_green_time<R: Rng>(config: &Config, rng: &mut R) -> f32 {
     config.min_green_duration +
         (config.max_green_duration - config.min_green_duration)
-        * rand::random::<f32>()
+        * rng.gen::<f32>()
 }
 
-fn update_lights(location: &mut Location, dt: f32, config: &Config) {
+fn update_lights<R: Rng>(location: &mut Location, dt: f32, config: &Config, rng: &mut R) {
     use StreetLightColor::*;
 
     let time = location.street_light_time - dt;
@@ -232,7 +234,7 @@ fn update_lights(location: &mut Location, dt: f32, config: &Config) {
         },
         YellowToGreen => {
             if time == 0.0 {
-                let new_time = random_green_time(config);
+                let new_time = random_green_time(config, rng);
                 (index, Green, new_time)
             }
             else {
@@ -317,9 +319,21 @@ impl Road {
         let dt: f32 = delta.subsec_micros() as f32 / 1_000_000.0;
         self.prev_instant = current;
 
+        self.step_street_lights(dt, config, &mut rand::thread_rng());
+    }
+
+    pub fn reset_street_lights<R: Rng>(&mut self, config: &Config, rng: &mut R) {
+        for location in self.locations.iter_mut() {
+            location.street_light_index = 0;
+            location.street_light_color = StreetLightColor::Green;
+            location.street_light_time = random_green_time(config, rng);
+        }
+    }
+
+    pub fn step_street_lights<R: Rng>(&mut self, dt: f32, config: &Config, rng: &mut R) {
         for location in self.locations.iter_mut() {
             if location.incoming_lanes.len() > 1 {
-                update_lights(location, dt, config);
+                update_lights(location, dt, config, rng);
             }
         }
     }
@@ -482,7 +482,7 @@ impl Backbone {
             incoming_lanes: Vec::new(),
             street_light_index: 0,
             street_light_color: StreetLightColor::Green,
-            street_light_time: random_green_time(config),
+            street_light_time: random_green_time(config, &mut rand::thread_rng()),
             position: Point { x: 0.0, y: 0.0 },
             adjacents: Vec::new(),
         });
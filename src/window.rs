@@ -420,6 +420,9 @@ pub fn handle_event(
             else if ch == 's' {
                 actions.push(Action::AddCarSlow);
             }
+            else if ch == 't' {
+                actions.push(Action::Trace);
+            }
             else if ch == 27 as char {
                 actions.push(Action::Esc);
             }